@@ -0,0 +1,431 @@
+//! Packs `frontend/images` into a single atlas and generates everything that
+//! depends on the sprite list:
+//!
+//! - `frontend/atlas.png`: the packed sprites
+//! - `frontend/atlas.json`: the manifest the frontend draws from
+//! - `core/src/assets.rs`: the `BitmapAsset` enum and the per-sprite metadata
+//!
+//! Per-sprite overrides (pivot points, hitboxes) are read from `frontend/images/sprites.json`.
+
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::BufWriter,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Width of the atlas, sprites are packed in rows under it
+const ATLAS_WIDTH: u32 = 1024;
+/// Transparent pixels between sprites, so that filtering never bleeds
+const PADDING: u32 = 1;
+
+/// Hand written metadata of a sprite, every field is optional
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct SpriteMeta {
+    /// Point placed on the primitive position, from the top left corner, defaults to the center
+    pivot: Option<(f32, f32)>,
+    /// (x, y, width, height) from the top left corner, defaults to the whole sprite
+    hitbox: Option<(f32, f32, f32, f32)>,
+}
+
+struct Sprite {
+    /// file stem, `enemy3_n1`
+    name: String,
+    width: u32,
+    height: u32,
+    rgba: Vec<u8>,
+    /// position in the atlas
+    x: u32,
+    y: u32,
+    pivot: (f32, f32),
+    hitbox: (f32, f32, f32, f32),
+}
+
+#[derive(Serialize)]
+struct ManifestSprite<'a> {
+    name: &'a str,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    pivot: (f32, f32),
+    hitbox: (f32, f32, f32, f32),
+}
+
+/// Indexed by `BitmapAsset`
+#[derive(Serialize)]
+struct Manifest<'a> {
+    image: &'a str,
+    width: u32,
+    height: u32,
+    sprites: Vec<ManifestSprite<'a>>,
+}
+
+fn load_png(path: &Path) -> Result<(u32, u32, Vec<u8>), String> {
+    let file = File::open(path).map_err(|err| err.to_string())?;
+    let mut decoder = png::Decoder::new(file);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(|err| err.to_string())?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf).map_err(|err| err.to_string())?;
+    buf.truncate(info.buffer_size());
+
+    let rgba = match info.color_type {
+        png::ColorType::Rgba => buf,
+        png::ColorType::Rgb => buf
+            .chunks(3)
+            .flat_map(|px| [px[0], px[1], px[2], 255])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => buf
+            .chunks(2)
+            .flat_map(|px| [px[0], px[0], px[0], px[1]])
+            .collect(),
+        png::ColorType::Grayscale => buf.iter().flat_map(|&v| [v, v, v, 255]).collect(),
+        png::ColorType::Indexed => unreachable!("expanded by the decoder"),
+    };
+    Ok((info.width, info.height, rgba))
+}
+
+fn save_png(path: &Path, width: u32, height: u32, rgba: &[u8]) -> Result<(), String> {
+    let file = File::create(path).map_err(|err| err.to_string())?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(|err| err.to_string())?;
+    writer.write_image_data(rgba).map_err(|err| err.to_string())
+}
+
+/// Shelf packing, tallest sprites first. Returns the atlas height.
+fn pack(sprites: &mut [Sprite]) -> Result<u32, String> {
+    let mut order: Vec<usize> = (0..sprites.len()).collect();
+    order.sort_by_key(|&i| {
+        (
+            std::cmp::Reverse(sprites[i].height),
+            sprites[i].name.clone(),
+        )
+    });
+
+    let (mut x, mut y, mut shelf_height) = (PADDING, PADDING, 0);
+    for i in order {
+        let sprite = &mut sprites[i];
+        if sprite.width + 2 * PADDING > ATLAS_WIDTH {
+            return Err(format!("{} is wider than the atlas", sprite.name));
+        }
+        if x + sprite.width + PADDING > ATLAS_WIDTH {
+            x = PADDING;
+            y += shelf_height + PADDING;
+            shelf_height = 0;
+        }
+        sprite.x = x;
+        sprite.y = y;
+        x += sprite.width + PADDING;
+        shelf_height = shelf_height.max(sprite.height);
+    }
+    Ok(y + shelf_height + PADDING)
+}
+
+/// `enemy3_n1` -> `Enemy3N1`
+fn variant_name(name: &str) -> String {
+    name.split(['_', '-'])
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            let first = chars.next().unwrap().to_ascii_uppercase();
+            std::iter::once(first).chain(chars).collect::<String>()
+        })
+        .collect()
+}
+
+fn generate_rust(sprites: &[Sprite]) -> String {
+    let mut out = String::new();
+    out.push_str(
+        "\
+// @generated by the `atlas` tool from `frontend/images`, do not edit by hand.
+// Run `just atlas` in `core` after adding, removing or changing a sprite.
+
+use serde::Deserialize;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+pub enum BitmapAsset {
+",
+    );
+    for sprite in sprites {
+        out.push_str(&format!(
+            "    /// {}x{}\n    {},\n",
+            sprite.width,
+            sprite.height,
+            variant_name(&sprite.name)
+        ));
+    }
+    out.push_str(
+        "}
+
+/// Where a sprite is in the atlas and how it is drawn
+pub struct SpriteInfo {
+    pub file: &'static str,
+    /// (x, y) of the top left corner in the atlas
+    pub atlas_pos: (u16, u16),
+    /// (width, height) in pixels
+    pub size: (u16, u16),
+    /// Point placed on the primitive position, from the top left corner
+    pub pivot: (f32, f32),
+    /// (x, y, width, height) of the collision rect, from the top left corner
+    pub hitbox: (f32, f32, f32, f32),
+}
+
+impl SpriteInfo {
+    /// The hitbox of the sprite drawn at `pos`, as (center_x, center_y, width, height)
+    /// in game coordinates, where y goes up
+    pub fn bounding_box(&self, pos: (f32, f32)) -> (f32, f32, f32, f32) {
+        let (x, y, width, height) = self.hitbox;
+        (
+            pos.0 + x + width / 2.0 - self.pivot.0,
+            pos.1 - (y + height / 2.0 - self.pivot.1),
+            width,
+            height,
+        )
+    }
+}
+
+impl BitmapAsset {
+",
+    );
+    out.push_str(&format!(
+        "    pub const ALL: [BitmapAsset; {}] = [\n",
+        sprites.len()
+    ));
+    for sprite in sprites {
+        out.push_str(&format!(
+            "        BitmapAsset::{},\n",
+            variant_name(&sprite.name)
+        ));
+    }
+    out.push_str(
+        "    ];
+
+    pub fn info(self) -> &'static SpriteInfo {
+        &SPRITES[self as usize]
+    }
+}
+
+",
+    );
+    out.push_str(&format!(
+        "static SPRITES: [SpriteInfo; {}] = [\n",
+        sprites.len()
+    ));
+    for sprite in sprites {
+        out.push_str(&format!(
+            "    SpriteInfo {{\n        file: \"{}.png\",\n        atlas_pos: ({}, {}),\n        size: ({}, {}),\n        pivot: ({:?}, {:?}),\n        hitbox: ({:?}, {:?}, {:?}, {:?}),\n    }},\n",
+            sprite.name, sprite.x, sprite.y, sprite.width, sprite.height, sprite.pivot.0, sprite.pivot.1,
+            sprite.hitbox.0, sprite.hitbox.1, sprite.hitbox.2, sprite.hitbox.3
+        ));
+    }
+    out.push_str("];\n");
+    out
+}
+
+fn run(root: &Path) -> Result<(), String> {
+    let images_dir = root.join("frontend/images");
+    let meta_path = images_dir.join("sprites.json");
+    let meta: HashMap<String, SpriteMeta> = match fs::read_to_string(&meta_path) {
+        Ok(json) => {
+            serde_json::from_str(&json).map_err(|err| format!("{}: {err}", meta_path.display()))?
+        }
+        Err(_) => HashMap::new(),
+    };
+
+    let mut paths: Vec<PathBuf> = fs::read_dir(&images_dir)
+        .map_err(|err| format!("{}: {err}", images_dir.display()))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "png"))
+        .collect();
+    paths.sort();
+
+    let mut sprites = vec![];
+    for path in paths {
+        let name = path.file_stem().unwrap().to_string_lossy().to_string();
+        let (width, height, rgba) =
+            load_png(&path).map_err(|err| format!("{}: {err}", path.display()))?;
+        let meta = meta.get(&name);
+        let pivot = meta
+            .and_then(|meta| meta.pivot)
+            .unwrap_or((width as f32 / 2.0, height as f32 / 2.0));
+        let hitbox =
+            meta.and_then(|meta| meta.hitbox)
+                .unwrap_or((0.0, 0.0, width as f32, height as f32));
+        let (x, y, w, h) = hitbox;
+        if x < 0.0
+            || y < 0.0
+            || w <= 0.0
+            || h <= 0.0
+            || x + w > width as f32
+            || y + h > height as f32
+        {
+            return Err(format!(
+                "{}: hitbox of `{name}` is out of the sprite",
+                meta_path.display()
+            ));
+        }
+        sprites.push(Sprite {
+            name,
+            width,
+            height,
+            rgba,
+            x: 0,
+            y: 0,
+            pivot,
+            hitbox,
+        });
+    }
+    if let Some(name) = meta
+        .keys()
+        .find(|name| !sprites.iter().any(|s| &s.name == *name))
+    {
+        return Err(format!("{}: no sprite named `{name}`", meta_path.display()));
+    }
+    if sprites.len() > u8::MAX as usize + 1 {
+        return Err("too many sprites for an u8 BitmapAsset".to_string());
+    }
+
+    let height = pack(&mut sprites)?;
+    let mut atlas = vec![0; (ATLAS_WIDTH * height * 4) as usize];
+    for sprite in &sprites {
+        for row in 0..sprite.height {
+            let src = (row * sprite.width * 4) as usize;
+            let dst = (((sprite.y + row) * ATLAS_WIDTH + sprite.x) * 4) as usize;
+            let len = (sprite.width * 4) as usize;
+            atlas[dst..dst + len].copy_from_slice(&sprite.rgba[src..src + len]);
+        }
+    }
+
+    let frontend_dir = root.join("frontend");
+    save_png(&frontend_dir.join("atlas.png"), ATLAS_WIDTH, height, &atlas)?;
+
+    let manifest = Manifest {
+        image: "atlas.png",
+        width: ATLAS_WIDTH,
+        height,
+        sprites: sprites
+            .iter()
+            .map(|sprite| ManifestSprite {
+                name: &sprite.name,
+                x: sprite.x,
+                y: sprite.y,
+                width: sprite.width,
+                height: sprite.height,
+                pivot: sprite.pivot,
+                hitbox: sprite.hitbox,
+            })
+            .collect(),
+    };
+    let manifest = serde_json::to_string_pretty(&manifest).unwrap();
+    fs::write(frontend_dir.join("atlas.json"), manifest + "\n").map_err(|err| err.to_string())?;
+
+    fs::write(root.join("core/src/assets.rs"), generate_rust(&sprites))
+        .map_err(|err| err.to_string())?;
+
+    println!(
+        "packed {} sprites into a {ATLAS_WIDTH}x{height} atlas",
+        sprites.len()
+    );
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    // `project2`, unless given explicitly
+    let root = std::env::args()
+        .nth(1)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| Path::new(env!("CARGO_MANIFEST_DIR")).join(".."));
+    match run(&root) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sprite(name: &str, width: u32, height: u32) -> Sprite {
+        Sprite {
+            name: name.to_string(),
+            width,
+            height,
+            rgba: vec![0; (width * height * 4) as usize],
+            x: 0,
+            y: 0,
+            pivot: (0.0, 0.0),
+            hitbox: (0.0, 0.0, width as f32, height as f32),
+        }
+    }
+
+    #[test]
+    fn packed_sprites_do_not_overlap() {
+        let mut sprites: Vec<Sprite> = (0..40)
+            .map(|i| sprite(&format!("s{i}"), 30 + i * 37 % 300, 10 + i * 53 % 120))
+            .collect();
+        sprites.push(sprite("wide", ATLAS_WIDTH - 2 * PADDING, 5));
+        let height = pack(&mut sprites).unwrap();
+
+        for (i, a) in sprites.iter().enumerate() {
+            assert!(a.x >= PADDING && a.y >= PADDING, "{}", a.name);
+            assert!(a.x + a.width + PADDING <= ATLAS_WIDTH, "{}", a.name);
+            assert!(a.y + a.height + PADDING <= height, "{}", a.name);
+            for b in &sprites[i + 1..] {
+                // padding included
+                let apart = a.x + a.width + PADDING <= b.x
+                    || b.x + b.width + PADDING <= a.x
+                    || a.y + a.height + PADDING <= b.y
+                    || b.y + b.height + PADDING <= a.y;
+                assert!(apart, "{} and {} overlap", a.name, b.name);
+            }
+        }
+
+        let mut too_wide = [sprite("huge", ATLAS_WIDTH, 5)];
+        assert_eq!(
+            pack(&mut too_wide),
+            Err("huge is wider than the atlas".to_string())
+        );
+    }
+
+    #[test]
+    fn variants_are_camel_cased_file_stems() {
+        assert_eq!(variant_name("enemy3_n1"), "Enemy3N1");
+        assert_eq!(variant_name("bomb_supply"), "BombSupply");
+        assert_eq!(variant_name("hero-down_1"), "HeroDown1");
+        assert_eq!(variant_name("digit0"), "Digit0");
+    }
+
+    #[test]
+    fn hitboxes_must_fit_in_their_sprite() {
+        let root = std::env::temp_dir().join(format!("atlas-test-{}", std::process::id()));
+        let images = root.join("frontend/images");
+        fs::create_dir_all(&images).unwrap();
+        save_png(&images.join("hero.png"), 10, 8, &[255; 10 * 8 * 4]).unwrap();
+        fs::write(
+            images.join("sprites.json"),
+            r#"{ "hero": { "hitbox": [4, 0, 8, 8] } }"#,
+        )
+        .unwrap();
+
+        let result = run(&root);
+        fs::remove_dir_all(&root).unwrap();
+        let err = result.unwrap_err();
+        assert!(
+            err.ends_with("hitbox of `hero` is out of the sprite"),
+            "{err}"
+        );
+    }
+}
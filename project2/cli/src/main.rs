@@ -0,0 +1,173 @@
+//! Headless runner of the game, for scripted simulation and batch runs.
+//!
+//! Prints one JSON summary per run, one run per line.
+
+use std::{fs, process::ExitCode};
+
+use game::{
+    accessibility::Accessibility,
+    difficulty::DifficultyPreset,
+    headless::simulate,
+    log::{self, FileSink, LogLevel},
+    replay::{parse_script, Replay, ReplayInput},
+    GameSettings,
+};
+
+const USAGE: &str = "\
+usage: core-cli [options]
+
+options:
+    --seed <n>         seed of the first run (default: 0)
+    --runs <n>         number of runs, with consecutive seeds (default: 1)
+    --ticks <n>        maximum ticks per run (default: 12000)
+    --width <n>        play area width (default: 480)
+    --height <n>       play area height (default: 700)
+    --players <n>      local players (default: 1)
+    --lives <n>        lives of each player (default: 1)
+    --difficulty <p>   easy, normal, hard or lunatic (default: normal)
+    --adaptive <bool>  let the director adjust the difficulty (default: false)
+    --assist <bool>    extra lives and slower enemy bullets (default: false)
+    --hitbox-scale <f> multiplier of the heroes' hitbox (default: 1)
+    --script <file>    input script, `<tick> <key> <down|up>` or
+                       `<tick> pointer <down|move|up> <x> <y>` per line
+    --replay <file>    replay file, provides the seed, the size, the players,
+                       the difficulty, the accessibility options and the inputs
+    --verify <file>    replay file to play again, checking the state after
+                       every tick against the recorded checksums
+    --log <level>      off, error, warn, info, debug or trace (default: warn)
+    --log-file <file>  write the log to a file instead of stderr
+    -h, --help         print this message";
+
+struct Args {
+    seed: u64,
+    runs: u64,
+    ticks: u32,
+    width: u16,
+    height: u16,
+    players: u8,
+    lives: u8,
+    difficulty: DifficultyPreset,
+    adaptive: bool,
+    accessibility: Accessibility,
+    inputs: Vec<ReplayInput>,
+    verify: Option<Replay>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args {
+        seed: 0,
+        runs: 1,
+        ticks: 12000,
+        width: 480,
+        height: 700,
+        players: 1,
+        lives: 1,
+        difficulty: DifficultyPreset::Normal,
+        adaptive: false,
+        accessibility: Accessibility::default(),
+        inputs: vec![],
+        verify: None,
+    };
+
+    let mut iter = std::env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        if arg == "-h" || arg == "--help" {
+            return Err(USAGE.to_string());
+        }
+        let value = iter
+            .next()
+            .ok_or_else(|| format!("missing value for `{arg}`"))?;
+        let invalid = || format!("invalid value for `{arg}`: {value}");
+        match arg.as_str() {
+            "--seed" => args.seed = value.parse().map_err(|_| invalid())?,
+            "--runs" => args.runs = value.parse().map_err(|_| invalid())?,
+            "--ticks" => args.ticks = value.parse().map_err(|_| invalid())?,
+            "--width" => args.width = value.parse().map_err(|_| invalid())?,
+            "--height" => args.height = value.parse().map_err(|_| invalid())?,
+            "--players" => args.players = value.parse().map_err(|_| invalid())?,
+            "--lives" => args.lives = value.parse().map_err(|_| invalid())?,
+            "--difficulty" => args.difficulty = value.parse()?,
+            "--adaptive" => args.adaptive = value.parse().map_err(|_| invalid())?,
+            "--assist" => args.accessibility.assist = value.parse().map_err(|_| invalid())?,
+            "--hitbox-scale" => {
+                args.accessibility.hitbox_scale = value.parse().map_err(|_| invalid())?
+            }
+            "--script" => {
+                let script = fs::read_to_string(&value).map_err(|err| format!("{value}: {err}"))?;
+                args.inputs = parse_script(&script).map_err(|err| format!("{value}: {err}"))?;
+            }
+            "--log" => log::set_level(value.parse::<LogLevel>()?),
+            "--log-file" => {
+                let sink = FileSink::create(&value).map_err(|err| format!("{value}: {err}"))?;
+                log::set_sink(Box::new(sink));
+            }
+            "--replay" => {
+                let json = fs::read_to_string(&value).map_err(|err| format!("{value}: {err}"))?;
+                let replay = Replay::from_json(&json).map_err(|err| format!("{value}: {err}"))?;
+                args.seed = replay.seed;
+                args.width = replay.width;
+                args.height = replay.height;
+                args.players = replay.players;
+                args.lives = replay.lives;
+                args.difficulty = replay.difficulty;
+                args.adaptive = replay.adaptive;
+                args.accessibility = replay.accessibility;
+                args.inputs = replay.inputs;
+            }
+            "--verify" => {
+                let json = fs::read_to_string(&value).map_err(|err| format!("{value}: {err}"))?;
+                let replay = Replay::from_json(&json).map_err(|err| format!("{value}: {err}"))?;
+                args.verify = Some(replay);
+            }
+            _ => return Err(format!("unknown option `{arg}`\n\n{USAGE}")),
+        }
+    }
+    Ok(args)
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("{err}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if let Some(replay) = args.verify {
+        if replay.checksums.is_empty() {
+            eprintln!("the replay has no checksum to verify");
+            return ExitCode::FAILURE;
+        }
+        return match replay.verify() {
+            Ok(ticks) => {
+                println!("{}", serde_json::json!({ "verified_ticks": ticks }));
+                ExitCode::SUCCESS
+            }
+            Err(divergence) => {
+                println!("{}", serde_json::json!({ "divergence": divergence }));
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    let Some(end) = args.seed.checked_add(args.runs) else {
+        eprintln!(
+            "`--seed {}` with `--runs {}` overflows the seeds",
+            args.seed, args.runs
+        );
+        return ExitCode::FAILURE;
+    };
+    for seed in args.seed..end {
+        let mut settings = GameSettings::new(args.width, args.height);
+        settings.seed = seed;
+        settings.players = args.players;
+        settings.lives = args.lives;
+        settings.difficulty = args.difficulty;
+        settings.adaptive = args.adaptive;
+        settings.accessibility = args.accessibility;
+        let summary = simulate(settings, &args.inputs, args.ticks);
+        println!("{}", serde_json::to_string(&summary).unwrap());
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,92 @@
+//! Options making the game playable by more people, part of [`GameSettings`]
+//! so that replays and leaderboard entries say how a run was played
+//!
+//! [`GameSettings`]: crate::GameSettings
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::entity::enemy::ENEMY_BULLET_SPEED;
+
+/// Lives added to [`crate::GameSettings::lives`] in assist mode
+pub const ASSIST_EXTRA_LIVES: u8 = 2;
+/// Multiplier of the speed of the enemy bullets in assist mode
+pub const ASSIST_BULLET_SPEED_SCALE: f32 = 0.6;
+pub const MIN_HITBOX_SCALE: f32 = 0.5;
+pub const MAX_HITBOX_SCALE: f32 = 1.5;
+/// Enemy bullets in high contrast, a saturated magenta found in none of the
+/// backgrounds
+const HIGH_CONTRAST_TINT: u32 = 0xff00_ffff;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Accessibility {
+    /// Extra lives and slower enemy bullets
+    pub assist: bool,
+    /// Multiplier of the hero's hitbox, from [`MIN_HITBOX_SCALE`] to
+    /// [`MAX_HITBOX_SCALE`]
+    pub hitbox_scale: f32,
+    /// Screen shake and flashes: muzzle flashes, the blinking of the
+    /// invulnerable heroes...
+    pub screen_effects: bool,
+    /// Tint the enemy bullets so they stand out against everything else
+    pub high_contrast: bool,
+}
+
+impl Default for Accessibility {
+    fn default() -> Self {
+        Self {
+            assist: false,
+            hitbox_scale: 1.0,
+            screen_effects: true,
+            high_contrast: false,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Accessibility {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Accessibility {
+    pub fn extra_lives(&self) -> u8 {
+        if self.assist {
+            ASSIST_EXTRA_LIVES
+        } else {
+            0
+        }
+    }
+
+    /// Speed of the enemy bullets, in pixel/tick
+    pub fn enemy_bullet_speed(&self) -> f32 {
+        if self.assist {
+            ENEMY_BULLET_SPEED * ASSIST_BULLET_SPEED_SCALE
+        } else {
+            ENEMY_BULLET_SPEED
+        }
+    }
+
+    /// [`Accessibility::hitbox_scale`] within its bounds, non-finite scales
+    /// leave the hitbox unscaled
+    pub fn clamped_hitbox_scale(&self) -> f32 {
+        if self.hitbox_scale.is_finite() {
+            self.hitbox_scale.clamp(MIN_HITBOX_SCALE, MAX_HITBOX_SCALE)
+        } else {
+            1.0
+        }
+    }
+
+    /// Tint of the enemy bullets, as in [`crate::render::Primitive::with_tint`]
+    pub fn enemy_bullet_tint(&self) -> u32 {
+        if self.high_contrast {
+            HIGH_CONTRAST_TINT
+        } else {
+            0
+        }
+    }
+}
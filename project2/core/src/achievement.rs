@@ -0,0 +1,234 @@
+//! Achievements: goals declared once in [`ACHIEVEMENTS`], checked against
+//! the [`RunStats`] of every run and kept across runs by an [`AchievementStore`]
+
+use serde::Serialize;
+
+use crate::{entity::enemy::EnemyKind, stats::RunStats};
+
+/// What a run must reach to unlock an achievement
+pub enum Goal {
+    Kills(EnemyKind, u32),
+    TotalKills(u32),
+    Score(u32),
+    /// Ticks alive
+    Survive(u32),
+    /// Ticks alive without being hit
+    Flawless(u32),
+    /// Kills in a single chain
+    Combo(u32),
+    Grazes(u32),
+    /// An accuracy of at least `min` over at least `shots` bullets
+    Accuracy {
+        min: f32,
+        shots: u32,
+    },
+}
+
+impl Goal {
+    pub fn reached(&self, stats: &RunStats, score: u32) -> bool {
+        match *self {
+            Goal::Kills(kind, count) => {
+                let kills = match kind {
+                    EnemyKind::MiddleCup => stats.kills.middle_cup,
+                    EnemyKind::BigCup => stats.kills.big_cup,
+                    EnemyKind::SuperBigCup => stats.kills.super_big_cup,
+                };
+                kills >= count
+            }
+            Goal::TotalKills(count) => stats.kills.total() >= count,
+            Goal::Score(points) => score >= points,
+            Goal::Survive(ticks) => stats.ticks >= ticks,
+            Goal::Flawless(ticks) => stats.ticks >= ticks && stats.hits_taken == 0,
+            Goal::Combo(kills) => stats.max_combo >= kills,
+            Goal::Grazes(count) => stats.grazes >= count,
+            Goal::Accuracy { min, shots } => {
+                stats.bullets_fired >= shots && stats.accuracy() >= min
+            }
+        }
+    }
+}
+
+pub struct Achievement {
+    /// Stable, what the stores keep
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub goal: Goal,
+}
+
+pub const ACHIEVEMENTS: &[Achievement] = &[
+    Achievement {
+        id: "first_blood",
+        name: "第一滴血",
+        description: "击落第一架敌机",
+        goal: Goal::TotalKills(1),
+    },
+    Achievement {
+        id: "cup_collector",
+        name: "收集杯子",
+        description: "单局击落 100 架小飞机",
+        goal: Goal::Kills(EnemyKind::MiddleCup, 100),
+    },
+    Achievement {
+        id: "giant_slayer",
+        name: "屠龙",
+        description: "击落一架大飞机",
+        goal: Goal::Kills(EnemyKind::SuperBigCup, 1),
+    },
+    Achievement {
+        id: "survivor",
+        name: "幸存者",
+        description: "存活 3 分钟",
+        goal: Goal::Survive(3600),
+    },
+    Achievement {
+        id: "flawless",
+        name: "毫发无伤",
+        description: "不被击中存活 1 分钟",
+        goal: Goal::Flawless(1200),
+    },
+    Achievement {
+        id: "chain_master",
+        name: "连击大师",
+        description: "达成 40 连击",
+        goal: Goal::Combo(40),
+    },
+    Achievement {
+        id: "grazer",
+        name: "擦弹高手",
+        description: "单局擦弹 50 次",
+        goal: Goal::Grazes(50),
+    },
+    Achievement {
+        id: "sharpshooter",
+        name: "神枪手",
+        description: "发射至少 200 发子弹且命中率不低于 50%",
+        goal: Goal::Accuracy {
+            min: 0.5,
+            shots: 200,
+        },
+    },
+    Achievement {
+        id: "high_score",
+        name: "高分",
+        description: "单局得分达到 100000",
+        goal: Goal::Score(100_000),
+    },
+];
+
+/// Where the unlocked achievements are kept between runs
+pub trait AchievementStore {
+    /// Ids of the achievements unlocked so far
+    fn load(&mut self) -> Vec<String>;
+    fn save(&mut self, unlocked: &[String]);
+}
+
+/// Keeps them for as long as it lives, the default outside the browser
+#[derive(Default)]
+pub struct MemoryStore(Vec<String>);
+
+impl AchievementStore for MemoryStore {
+    fn load(&mut self) -> Vec<String> {
+        self.0.clone()
+    }
+
+    fn save(&mut self, unlocked: &[String]) {
+        self.0 = unlocked.to_vec();
+    }
+}
+
+/// Keeps them in the `localStorage` of the page, as a JSON array of ids
+#[cfg(target_arch = "wasm32")]
+pub struct LocalStorageStore;
+
+#[cfg(target_arch = "wasm32")]
+impl LocalStorageStore {
+    const KEY: &'static str = "achievements";
+
+    fn storage() -> Option<web_sys::Storage> {
+        web_sys::window()?.local_storage().ok()?
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl AchievementStore for LocalStorageStore {
+    fn load(&mut self) -> Vec<String> {
+        Self::storage()
+            .and_then(|storage| storage.get_item(Self::KEY).ok()?)
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&mut self, unlocked: &[String]) {
+        if let Some(storage) = Self::storage() {
+            // losing an achievement is not worth stopping the game for
+            let _ = storage.set_item(Self::KEY, &serde_json::to_string(unlocked).unwrap());
+        }
+    }
+}
+
+/// The achievements unlocked so far, over every run played with the same store
+pub struct Achievements {
+    store: Box<dyn AchievementStore>,
+    unlocked: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct AchievementView {
+    id: &'static str,
+    name: &'static str,
+    description: &'static str,
+    unlocked: bool,
+}
+
+impl Achievements {
+    pub fn new(mut store: Box<dyn AchievementStore>) -> Self {
+        let unlocked = store.load();
+        Self { store, unlocked }
+    }
+
+    pub fn is_unlocked(&self, id: &str) -> bool {
+        self.unlocked.iter().any(|unlocked| unlocked == id)
+    }
+
+    /// Unlock and save the achievements whose goal the run reached, returns
+    /// the newly unlocked ones
+    pub fn check(&mut self, stats: &RunStats, score: u32) -> Vec<&'static Achievement> {
+        let reached: Vec<_> = ACHIEVEMENTS
+            .iter()
+            .filter(|achievement| {
+                !self.is_unlocked(achievement.id) && achievement.goal.reached(stats, score)
+            })
+            .collect();
+        if !reached.is_empty() {
+            self.unlocked
+                .extend(reached.iter().map(|achievement| achievement.id.to_string()));
+            self.store.save(&self.unlocked);
+        }
+        reached
+    }
+
+    /// Every achievement, with whether it is unlocked, as a JSON array
+    pub fn to_json(&self) -> String {
+        let views: Vec<_> = ACHIEVEMENTS
+            .iter()
+            .map(|achievement| AchievementView {
+                id: achievement.id,
+                name: achievement.name,
+                description: achievement.description,
+                unlocked: self.is_unlocked(achievement.id),
+            })
+            .collect();
+        serde_json::to_string(&views).unwrap()
+    }
+}
+
+impl Default for Achievements {
+    fn default() -> Self {
+        #[cfg(target_arch = "wasm32")]
+        let store = Box::new(LocalStorageStore);
+        #[cfg(not(target_arch = "wasm32"))]
+        let store = Box::new(MemoryStore::default());
+        Self::new(store)
+    }
+}
@@ -1,59 +1,370 @@
-use crate::render::BitmapAsset;
+//! Sprite animation clips and the animator that picks them by [`EntityState`]
+//!
+//! Every animation is declared in `animations.json`: a set per entity type,
+//! each with named clips, the clip to play for each state, and optional
+//! transitions between clips.
 
-struct AnimationTicker {
-    tick_per_frame: usize,
-    tick_cnt: usize,
+use std::{collections::HashMap, sync::OnceLock};
 
-    pub total: usize,
-    pub cur: usize,
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{entity::EntityState, render::BitmapAsset};
+
+/// Fastest playback [`Animator::set_speed`] allows, so that a tick never
+/// walks through more than a few cycles of a clip
+pub const MAX_PLAYBACK_SPEED: f32 = 16.0;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LoopMode {
+    /// Wrap around to the first frame, finishing once per cycle
+    Loop,
+    /// Play once, then rewind to the first frame and stop
+    Once,
+    /// Play forward then backward, finishing once per round trip
+    PingPong,
+    /// Play once, then stay on the last frame
+    HoldLast,
 }
 
-impl AnimationTicker {
-    pub fn new(tick_per_frame: usize, total: usize) -> Self {
-        Self {
-            tick_per_frame,
-            tick_cnt: 0, 
-
-            total,
-            cur: 0
-        }
-    }
-    
-    /// return value represents whether if a cycle is done
-    pub fn tick(&mut self) -> bool {
-        self.tick_cnt += 1;
-        if self.tick_cnt == self.tick_per_frame {
-            self.tick_cnt = 0;
-            self.cur += 1;
-            if self.cur == self.total {
-                self.cur = 0;
-                return true;
-            } else {
-                return false;
+#[derive(Deserialize)]
+pub struct Frame {
+    pub bitmap: BitmapAsset,
+    /// How long the frame is shown, at playback speed 1
+    pub ticks: u32,
+    /// Named event fired when the frame is entered
+    #[serde(default)]
+    pub event: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct AnimationClip {
+    pub mode: LoopMode,
+    pub frames: Vec<Frame>,
+}
+
+#[derive(Deserialize)]
+pub struct Transition {
+    pub from: String,
+    pub to: String,
+    /// Let the current clip finish its cycle before switching
+    #[serde(default)]
+    pub wait_for_end: bool,
+}
+
+/// All the animations of one entity type
+#[derive(Deserialize)]
+pub struct AnimationSet {
+    pub clips: HashMap<String, AnimationClip>,
+    /// Clip played in each state, states not listed keep the current clip
+    pub states: HashMap<EntityState, String>,
+    #[serde(default)]
+    pub transitions: Vec<Transition>,
+    /// Sprite whose hitbox is used whatever the frame, by default the hitbox
+    /// follows the current frame
+    #[serde(default)]
+    pub hitbox: Option<BitmapAsset>,
+}
+
+impl AnimationSet {
+    fn validate(&self, set_name: &str) -> Result<(), String> {
+        let err = |msg: String| Err(format!("animation set `{set_name}`: {msg}"));
+        for (name, clip) in self.clips.iter() {
+            if clip.frames.is_empty() {
+                return err(format!("clip `{name}` has no frame"));
+            }
+            if clip.frames.iter().any(|frame| frame.ticks == 0) {
+                return err(format!("clip `{name}` has a frame lasting 0 tick"));
+            }
+        }
+        let referenced = self
+            .states
+            .values()
+            .chain(self.transitions.iter().flat_map(|t| [&t.from, &t.to]));
+        for name in referenced {
+            if !self.clips.contains_key(name) {
+                return err(format!("unknown clip `{name}`"));
+            }
+        }
+        if !self.states.contains_key(&EntityState::Normal) {
+            return err("no clip for the normal state".to_string());
+        }
+        Ok(())
+    }
+
+    fn transition(&self, from: &str, to: &str) -> Option<&Transition> {
+        self.transitions
+            .iter()
+            .find(|transition| transition.from == from && transition.to == to)
+    }
+}
+
+/// Parse and validate a set of animation definitions
+pub fn parse_library(json: &str) -> Result<HashMap<String, AnimationSet>, String> {
+    let library: HashMap<String, AnimationSet> =
+        serde_json::from_str(json).map_err(|err| format!("invalid animations: {err}"))?;
+    for (name, set) in library.iter() {
+        set.validate(name)?;
+    }
+    Ok(library)
+}
+
+/// The animations bundled with the game
+pub fn library() -> &'static HashMap<String, AnimationSet> {
+    static LIBRARY: OnceLock<HashMap<String, AnimationSet>> = OnceLock::new();
+    LIBRARY.get_or_init(|| parse_library(include_str!("animations.json")).unwrap())
+}
+
+/// Playback position inside a clip
+#[derive(Default, Clone, Serialize, Deserialize)]
+struct ClipPlayer {
+    frame: usize,
+    /// ticks spent on the current frame, scaled by the playback speed
+    elapsed: f32,
+    backwards: bool,
+    started: bool,
+    /// a `Once` or `HoldLast` clip played to its end
+    done: bool,
+}
+
+impl ClipPlayer {
+    /// Returns whether the clip finished (a cycle of) its playback
+    fn tick(
+        &mut self,
+        clip: &'static AnimationClip,
+        speed: f32,
+        events: &mut Vec<&'static str>,
+    ) -> bool {
+        if !self.started {
+            self.started = true;
+            events.extend(clip.frames[0].event.as_deref());
+        }
+        if self.done {
+            return false;
+        }
+
+        let last = clip.frames.len() - 1;
+        let mut finished = false;
+        self.elapsed += speed;
+        while self.elapsed >= clip.frames[self.frame].ticks as f32 {
+            self.elapsed -= clip.frames[self.frame].ticks as f32;
+            match clip.mode {
+                LoopMode::Loop if self.frame == last => {
+                    self.frame = 0;
+                    finished = true;
+                }
+                LoopMode::Once if self.frame == last => {
+                    self.frame = 0;
+                    self.done = true;
+                    return true;
+                }
+                LoopMode::HoldLast if self.frame == last => {
+                    self.done = true;
+                    return true;
+                }
+                LoopMode::PingPong if last == 0 => finished = true,
+                LoopMode::PingPong => {
+                    if self.backwards {
+                        self.frame -= 1;
+                    } else if self.frame == last {
+                        self.backwards = true;
+                        self.frame -= 1;
+                    } else {
+                        self.frame += 1;
+                    }
+                    if self.backwards && self.frame == 0 {
+                        self.backwards = false;
+                        finished = true;
+                    }
+                }
+                _ => self.frame += 1,
             }
+            events.extend(clip.frames[self.frame].event.as_deref());
         }
-        false
+        finished
     }
 }
 
-pub struct AnimatedBitmap {
-    animation_tick: AnimationTicker,
-    bitmaps: Vec<BitmapAsset>,
+/// What happened during an [`Animator::tick`]
+#[derive(Default)]
+pub struct AnimationTick {
+    /// The clip of the current state finished (a cycle of) its playback
+    pub finished: bool,
+    /// Events of the frames entered during the tick
+    pub events: Vec<&'static str>,
 }
 
-impl AnimatedBitmap {
-    pub fn new(bitmaps: Vec<BitmapAsset>, tick_per_frame: usize) -> Self {
+/// Serialized form of an [`Animator`], the set and clips are referred to by name
+#[derive(Serialize, Deserialize)]
+struct AnimatorState {
+    set: String,
+    state: EntityState,
+    clip: String,
+    player: ClipPlayer,
+    queued: Option<String>,
+    speed: f32,
+}
+
+#[derive(Clone)]
+pub struct Animator {
+    set_name: &'static str,
+    set: &'static AnimationSet,
+    state: EntityState,
+    clip_name: &'static str,
+    clip: &'static AnimationClip,
+    player: ClipPlayer,
+    /// Clip waiting for the current one to finish, see [`Transition::wait_for_end`]
+    queued: Option<&'static str>,
+    /// Playback speed multiplier, see [`Animator::set_speed`]
+    speed: f32,
+}
+
+impl Animator {
+    /// Animator of a set from [`library`], starting in the normal state
+    ///
+    /// Panics if there is no such set, as the sets are bundled with the game.
+    pub fn new(set_name: &str) -> Self {
+        let (set_name, set) = library()
+            .get_key_value(set_name)
+            .unwrap_or_else(|| panic!("no animation set named `{set_name}`"));
+        Self::from_set(set_name, set)
+    }
+
+    /// Animator of a set that isn't in the [`library`], snapshots holding it
+    /// can't be restored
+    pub fn from_set(set_name: &'static str, set: &'static AnimationSet) -> Self {
+        let (clip_name, clip) = set
+            .clips
+            .get_key_value(&set.states[&EntityState::Normal])
+            .unwrap();
         Self {
-            animation_tick: AnimationTicker::new(tick_per_frame, bitmaps.len()),
-            bitmaps,
+            set_name,
+            set,
+            state: EntityState::Normal,
+            clip_name,
+            clip,
+            player: ClipPlayer::default(),
+            queued: None,
+            speed: 1.0,
+        }
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Play faster or slower, 0 freezes the animation, clamped to
+    /// [`MAX_PLAYBACK_SPEED`]
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = if speed.is_finite() {
+            speed.clamp(0.0, MAX_PLAYBACK_SPEED)
+        } else {
+            1.0
+        };
+    }
+
+    fn play(&mut self, clip_name: &'static str) {
+        self.clip_name = clip_name;
+        self.clip = &self.set.clips[clip_name];
+        self.player = ClipPlayer::default();
+        self.queued = None;
+    }
+
+    /// Switch to the clip of `state`, following the configured transition
+    pub fn set_state(&mut self, state: EntityState) {
+        self.state = state;
+        let Some(target) = self.set.states.get(&state) else {
+            return;
+        };
+        if target == self.clip_name {
+            self.queued = None;
+            return;
+        }
+        let wait = self
+            .set
+            .transition(self.clip_name, target)
+            .is_some_and(|transition| transition.wait_for_end);
+        if wait && !self.player.done {
+            self.queued = Some(target);
+        } else {
+            self.play(target);
         }
     }
 
-    pub fn tick(&mut self) -> bool {
-        self.animation_tick.tick()
+    pub fn tick(&mut self) -> AnimationTick {
+        let mut tick = AnimationTick::default();
+        let finished = self.player.tick(self.clip, self.speed, &mut tick.events);
+        if finished || self.player.done {
+            if let Some(queued) = self.queued {
+                self.play(queued);
+                return tick;
+            }
+        }
+        tick.finished = finished
+            && self.set.states.get(&self.state).map(String::as_str) == Some(self.clip_name);
+        tick
     }
 
     pub fn cur_bitmap(&self) -> BitmapAsset {
-        self.bitmaps.get(self.animation_tick.cur).unwrap().clone()
+        self.clip.frames[self.player.frame].bitmap
+    }
+
+    /// The sprite the collision box is taken from, see [`AnimationSet::hitbox`]
+    pub fn hitbox_bitmap(&self) -> BitmapAsset {
+        self.set.hitbox.unwrap_or_else(|| self.cur_bitmap())
+    }
+}
+
+impl Serialize for Animator {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        AnimatorState {
+            set: self.set_name.to_string(),
+            state: self.state,
+            clip: self.clip_name.to_string(),
+            player: self.player.clone(),
+            queued: self.queued.map(str::to_string),
+            speed: self.speed,
+        }
+        .serialize(serializer)
     }
-}
\ No newline at end of file
+}
+
+impl<'de> Deserialize<'de> for Animator {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Animator::restore(AnimatorState::deserialize(deserializer)?).map_err(de::Error::custom)
+    }
+}
+
+impl Animator {
+    /// Resolve the names of a serialized animator against the [`library`]
+    fn restore(state: AnimatorState) -> Result<Self, String> {
+        let (set_name, set) = library()
+            .get_key_value(&state.set)
+            .ok_or_else(|| format!("no animation set named `{}`", state.set))?;
+        let find_clip = |name: &str| -> Result<&'static str, String> {
+            set.clips
+                .get_key_value(name)
+                .map(|(name, _)| name.as_str())
+                .ok_or_else(|| format!("animation set `{set_name}`: unknown clip `{name}`"))
+        };
+        let clip_name = find_clip(&state.clip)?;
+        let queued = state.queued.as_deref().map(find_clip).transpose()?;
+        let clip = &set.clips[clip_name];
+        if state.player.frame >= clip.frames.len() {
+            return Err(format!(
+                "animation set `{set_name}`: clip `{clip_name}` has no frame {}",
+                state.player.frame
+            ));
+        }
+        Ok(Self {
+            set_name,
+            set,
+            state: state.state,
+            clip_name,
+            clip,
+            player: state.player,
+            queued,
+            speed: state.speed,
+        })
+    }
+}
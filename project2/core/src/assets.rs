@@ -0,0 +1,570 @@
+// @generated by the `atlas` tool from `frontend/images`, do not edit by hand.
+// Run `just atlas` in `core` after adding, removing or changing a sprite.
+
+use serde::Deserialize;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+pub enum BitmapAsset {
+    /// 300x41
+    Again,
+    /// 480x700
+    Background,
+    /// 63x57
+    Bomb,
+    /// 60x107
+    BombSupply,
+    /// 5x11
+    BulletEnemy,
+    /// 5x11
+    BulletPlayer,
+    /// 58x88
+    BulletSupply,
+    /// 128x64
+    Cloud,
+    /// 17x23
+    Digit0,
+    /// 17x23
+    Digit1,
+    /// 17x23
+    Digit2,
+    /// 17x23
+    Digit3,
+    /// 17x23
+    Digit4,
+    /// 17x23
+    Digit5,
+    /// 17x23
+    Digit6,
+    /// 17x23
+    Digit7,
+    /// 17x23
+    Digit8,
+    /// 17x23
+    Digit9,
+    /// 57x43
+    Enemy1,
+    /// 57x51
+    Enemy1Down1,
+    /// 57x51
+    Enemy1Down2,
+    /// 57x51
+    Enemy1Down3,
+    /// 57x51
+    Enemy1Down4,
+    /// 69x99
+    Enemy2,
+    /// 69x95
+    Enemy2Down1,
+    /// 69x95
+    Enemy2Down2,
+    /// 69x95
+    Enemy2Down3,
+    /// 69x95
+    Enemy2Down4,
+    /// 69x99
+    Enemy2Hit,
+    /// 165x261
+    Enemy3Down1,
+    /// 165x261
+    Enemy3Down2,
+    /// 165x260
+    Enemy3Down3,
+    /// 165x261
+    Enemy3Down4,
+    /// 166x260
+    Enemy3Down5,
+    /// 166x261
+    Enemy3Down6,
+    /// 169x258
+    Enemy3Hit,
+    /// 169x258
+    Enemy3N1,
+    /// 169x258
+    Enemy3N2,
+    /// 300x41
+    Gameover,
+    /// 102x126
+    Hero1,
+    /// 102x126
+    Hero2,
+    /// 102x126
+    HeroDown1,
+    /// 102x126
+    HeroDown2,
+    /// 102x126
+    HeroDown3,
+    /// 102x126
+    HeroDown4,
+    /// 32x32
+    Hitbox,
+    /// 46x57
+    Life,
+    /// 60x45
+    PauseNor,
+    /// 60x45
+    PausePressed,
+    /// 60x45
+    ResumeNor,
+    /// 60x45
+    ResumePressed,
+    /// 7x7
+    Star,
+}
+
+/// Where a sprite is in the atlas and how it is drawn
+pub struct SpriteInfo {
+    pub file: &'static str,
+    /// (x, y) of the top left corner in the atlas
+    pub atlas_pos: (u16, u16),
+    /// (width, height) in pixels
+    pub size: (u16, u16),
+    /// Point placed on the primitive position, from the top left corner
+    pub pivot: (f32, f32),
+    /// (x, y, width, height) of the collision rect, from the top left corner
+    pub hitbox: (f32, f32, f32, f32),
+}
+
+impl SpriteInfo {
+    /// The hitbox of the sprite drawn at `pos`, as (center_x, center_y, width, height)
+    /// in game coordinates, where y goes up
+    pub fn bounding_box(&self, pos: (f32, f32)) -> (f32, f32, f32, f32) {
+        let (x, y, width, height) = self.hitbox;
+        (
+            pos.0 + x + width / 2.0 - self.pivot.0,
+            pos.1 - (y + height / 2.0 - self.pivot.1),
+            width,
+            height,
+        )
+    }
+}
+
+impl BitmapAsset {
+    pub const ALL: [BitmapAsset; 52] = [
+        BitmapAsset::Again,
+        BitmapAsset::Background,
+        BitmapAsset::Bomb,
+        BitmapAsset::BombSupply,
+        BitmapAsset::BulletEnemy,
+        BitmapAsset::BulletPlayer,
+        BitmapAsset::BulletSupply,
+        BitmapAsset::Cloud,
+        BitmapAsset::Digit0,
+        BitmapAsset::Digit1,
+        BitmapAsset::Digit2,
+        BitmapAsset::Digit3,
+        BitmapAsset::Digit4,
+        BitmapAsset::Digit5,
+        BitmapAsset::Digit6,
+        BitmapAsset::Digit7,
+        BitmapAsset::Digit8,
+        BitmapAsset::Digit9,
+        BitmapAsset::Enemy1,
+        BitmapAsset::Enemy1Down1,
+        BitmapAsset::Enemy1Down2,
+        BitmapAsset::Enemy1Down3,
+        BitmapAsset::Enemy1Down4,
+        BitmapAsset::Enemy2,
+        BitmapAsset::Enemy2Down1,
+        BitmapAsset::Enemy2Down2,
+        BitmapAsset::Enemy2Down3,
+        BitmapAsset::Enemy2Down4,
+        BitmapAsset::Enemy2Hit,
+        BitmapAsset::Enemy3Down1,
+        BitmapAsset::Enemy3Down2,
+        BitmapAsset::Enemy3Down3,
+        BitmapAsset::Enemy3Down4,
+        BitmapAsset::Enemy3Down5,
+        BitmapAsset::Enemy3Down6,
+        BitmapAsset::Enemy3Hit,
+        BitmapAsset::Enemy3N1,
+        BitmapAsset::Enemy3N2,
+        BitmapAsset::Gameover,
+        BitmapAsset::Hero1,
+        BitmapAsset::Hero2,
+        BitmapAsset::HeroDown1,
+        BitmapAsset::HeroDown2,
+        BitmapAsset::HeroDown3,
+        BitmapAsset::HeroDown4,
+        BitmapAsset::Hitbox,
+        BitmapAsset::Life,
+        BitmapAsset::PauseNor,
+        BitmapAsset::PausePressed,
+        BitmapAsset::ResumeNor,
+        BitmapAsset::ResumePressed,
+        BitmapAsset::Star,
+    ];
+
+    pub fn info(self) -> &'static SpriteInfo {
+        &SPRITES[self as usize]
+    }
+}
+
+static SPRITES: [SpriteInfo; 52] = [
+    SpriteInfo {
+        file: "again.png",
+        atlas_pos: (1, 1187),
+        size: (300, 41),
+        pivot: (150.0, 20.5),
+        hitbox: (0.0, 0.0, 300.0, 41.0),
+    },
+    SpriteInfo {
+        file: "background.png",
+        atlas_pos: (1, 1),
+        size: (480, 700),
+        pivot: (240.0, 350.0),
+        hitbox: (0.0, 0.0, 480.0, 700.0),
+    },
+    SpriteInfo {
+        file: "bomb.png",
+        atlas_pos: (329, 1091),
+        size: (63, 57),
+        pivot: (31.5, 28.5),
+        hitbox: (0.0, 0.0, 63.0, 57.0),
+    },
+    SpriteInfo {
+        file: "bomb_supply.png",
+        atlas_pos: (619, 964),
+        size: (60, 107),
+        pivot: (30.0, 53.5),
+        hitbox: (0.0, 0.0, 60.0, 107.0),
+    },
+    SpriteInfo {
+        file: "bullet_enemy.png",
+        atlas_pos: (816, 1187),
+        size: (5, 11),
+        pivot: (2.5, 5.5),
+        hitbox: (0.0, 0.0, 5.0, 11.0),
+    },
+    SpriteInfo {
+        file: "bullet_player.png",
+        atlas_pos: (822, 1187),
+        size: (5, 11),
+        pivot: (2.5, 5.5),
+        hitbox: (0.0, 0.0, 5.0, 11.0),
+    },
+    SpriteInfo {
+        file: "bullet_supply.png",
+        atlas_pos: (141, 1091),
+        size: (58, 88),
+        pivot: (29.0, 44.0),
+        hitbox: (0.0, 0.0, 58.0, 88.0),
+    },
+    SpriteInfo {
+        file: "cloud.png",
+        atlas_pos: (200, 1091),
+        size: (128, 64),
+        pivot: (64.0, 32.0),
+        hitbox: (0.0, 0.0, 128.0, 64.0),
+    },
+    SpriteInfo {
+        file: "digit0.png",
+        atlas_pos: (636, 1187),
+        size: (17, 23),
+        pivot: (8.5, 11.5),
+        hitbox: (0.0, 0.0, 17.0, 23.0),
+    },
+    SpriteInfo {
+        file: "digit1.png",
+        atlas_pos: (654, 1187),
+        size: (17, 23),
+        pivot: (8.5, 11.5),
+        hitbox: (0.0, 0.0, 17.0, 23.0),
+    },
+    SpriteInfo {
+        file: "digit2.png",
+        atlas_pos: (672, 1187),
+        size: (17, 23),
+        pivot: (8.5, 11.5),
+        hitbox: (0.0, 0.0, 17.0, 23.0),
+    },
+    SpriteInfo {
+        file: "digit3.png",
+        atlas_pos: (690, 1187),
+        size: (17, 23),
+        pivot: (8.5, 11.5),
+        hitbox: (0.0, 0.0, 17.0, 23.0),
+    },
+    SpriteInfo {
+        file: "digit4.png",
+        atlas_pos: (708, 1187),
+        size: (17, 23),
+        pivot: (8.5, 11.5),
+        hitbox: (0.0, 0.0, 17.0, 23.0),
+    },
+    SpriteInfo {
+        file: "digit5.png",
+        atlas_pos: (726, 1187),
+        size: (17, 23),
+        pivot: (8.5, 11.5),
+        hitbox: (0.0, 0.0, 17.0, 23.0),
+    },
+    SpriteInfo {
+        file: "digit6.png",
+        atlas_pos: (744, 1187),
+        size: (17, 23),
+        pivot: (8.5, 11.5),
+        hitbox: (0.0, 0.0, 17.0, 23.0),
+    },
+    SpriteInfo {
+        file: "digit7.png",
+        atlas_pos: (762, 1187),
+        size: (17, 23),
+        pivot: (8.5, 11.5),
+        hitbox: (0.0, 0.0, 17.0, 23.0),
+    },
+    SpriteInfo {
+        file: "digit8.png",
+        atlas_pos: (780, 1187),
+        size: (17, 23),
+        pivot: (8.5, 11.5),
+        hitbox: (0.0, 0.0, 17.0, 23.0),
+    },
+    SpriteInfo {
+        file: "digit9.png",
+        atlas_pos: (798, 1187),
+        size: (17, 23),
+        pivot: (8.5, 11.5),
+        hitbox: (0.0, 0.0, 17.0, 23.0),
+    },
+    SpriteInfo {
+        file: "enemy1.png",
+        atlas_pos: (916, 1091),
+        size: (57, 43),
+        pivot: (28.5, 21.5),
+        hitbox: (0.0, 0.0, 57.0, 43.0),
+    },
+    SpriteInfo {
+        file: "enemy1_down1.png",
+        atlas_pos: (440, 1091),
+        size: (57, 51),
+        pivot: (28.5, 25.5),
+        hitbox: (0.0, 0.0, 57.0, 51.0),
+    },
+    SpriteInfo {
+        file: "enemy1_down2.png",
+        atlas_pos: (498, 1091),
+        size: (57, 51),
+        pivot: (28.5, 25.5),
+        hitbox: (0.0, 0.0, 57.0, 51.0),
+    },
+    SpriteInfo {
+        file: "enemy1_down3.png",
+        atlas_pos: (556, 1091),
+        size: (57, 51),
+        pivot: (28.5, 25.5),
+        hitbox: (0.0, 0.0, 57.0, 51.0),
+    },
+    SpriteInfo {
+        file: "enemy1_down4.png",
+        atlas_pos: (614, 1091),
+        size: (57, 51),
+        pivot: (28.5, 25.5),
+        hitbox: (0.0, 0.0, 57.0, 51.0),
+    },
+    SpriteInfo {
+        file: "enemy2.png",
+        atlas_pos: (680, 964),
+        size: (69, 99),
+        pivot: (34.5, 49.5),
+        hitbox: (0.0, 0.0, 69.0, 99.0),
+    },
+    SpriteInfo {
+        file: "enemy2_down1.png",
+        atlas_pos: (820, 964),
+        size: (69, 95),
+        pivot: (34.5, 47.5),
+        hitbox: (0.0, 0.0, 69.0, 95.0),
+    },
+    SpriteInfo {
+        file: "enemy2_down2.png",
+        atlas_pos: (890, 964),
+        size: (69, 95),
+        pivot: (34.5, 47.5),
+        hitbox: (0.0, 0.0, 69.0, 95.0),
+    },
+    SpriteInfo {
+        file: "enemy2_down3.png",
+        atlas_pos: (1, 1091),
+        size: (69, 95),
+        pivot: (34.5, 47.5),
+        hitbox: (0.0, 0.0, 69.0, 95.0),
+    },
+    SpriteInfo {
+        file: "enemy2_down4.png",
+        atlas_pos: (71, 1091),
+        size: (69, 95),
+        pivot: (34.5, 47.5),
+        hitbox: (0.0, 0.0, 69.0, 95.0),
+    },
+    SpriteInfo {
+        file: "enemy2_hit.png",
+        atlas_pos: (750, 964),
+        size: (69, 99),
+        pivot: (34.5, 49.5),
+        hitbox: (0.0, 0.0, 69.0, 99.0),
+    },
+    SpriteInfo {
+        file: "enemy3_down1.png",
+        atlas_pos: (482, 1),
+        size: (165, 261),
+        pivot: (82.5, 130.5),
+        hitbox: (0.0, 0.0, 165.0, 261.0),
+    },
+    SpriteInfo {
+        file: "enemy3_down2.png",
+        atlas_pos: (648, 1),
+        size: (165, 261),
+        pivot: (82.5, 130.5),
+        hitbox: (0.0, 0.0, 165.0, 261.0),
+    },
+    SpriteInfo {
+        file: "enemy3_down3.png",
+        atlas_pos: (168, 702),
+        size: (165, 260),
+        pivot: (82.5, 130.0),
+        hitbox: (0.0, 0.0, 165.0, 260.0),
+    },
+    SpriteInfo {
+        file: "enemy3_down4.png",
+        atlas_pos: (814, 1),
+        size: (165, 261),
+        pivot: (82.5, 130.5),
+        hitbox: (0.0, 0.0, 165.0, 261.0),
+    },
+    SpriteInfo {
+        file: "enemy3_down5.png",
+        atlas_pos: (334, 702),
+        size: (166, 260),
+        pivot: (83.0, 130.0),
+        hitbox: (0.0, 0.0, 166.0, 260.0),
+    },
+    SpriteInfo {
+        file: "enemy3_down6.png",
+        atlas_pos: (1, 702),
+        size: (166, 261),
+        pivot: (83.0, 130.5),
+        hitbox: (0.0, 0.0, 166.0, 261.0),
+    },
+    SpriteInfo {
+        file: "enemy3_hit.png",
+        atlas_pos: (501, 702),
+        size: (169, 258),
+        pivot: (84.5, 129.0),
+        hitbox: (0.0, 0.0, 169.0, 258.0),
+    },
+    SpriteInfo {
+        file: "enemy3_n1.png",
+        atlas_pos: (671, 702),
+        size: (169, 258),
+        pivot: (84.5, 129.0),
+        hitbox: (0.0, 0.0, 169.0, 258.0),
+    },
+    SpriteInfo {
+        file: "enemy3_n2.png",
+        atlas_pos: (841, 702),
+        size: (169, 258),
+        pivot: (84.5, 129.0),
+        hitbox: (0.0, 0.0, 169.0, 258.0),
+    },
+    SpriteInfo {
+        file: "gameover.png",
+        atlas_pos: (302, 1187),
+        size: (300, 41),
+        pivot: (150.0, 20.5),
+        hitbox: (0.0, 0.0, 300.0, 41.0),
+    },
+    SpriteInfo {
+        file: "hero1.png",
+        atlas_pos: (1, 964),
+        size: (102, 126),
+        pivot: (51.0, 63.0),
+        hitbox: (0.0, 0.0, 102.0, 126.0),
+    },
+    SpriteInfo {
+        file: "hero2.png",
+        atlas_pos: (104, 964),
+        size: (102, 126),
+        pivot: (51.0, 63.0),
+        hitbox: (0.0, 0.0, 102.0, 126.0),
+    },
+    SpriteInfo {
+        file: "hero_down1.png",
+        atlas_pos: (207, 964),
+        size: (102, 126),
+        pivot: (51.0, 63.0),
+        hitbox: (0.0, 0.0, 102.0, 126.0),
+    },
+    SpriteInfo {
+        file: "hero_down2.png",
+        atlas_pos: (310, 964),
+        size: (102, 126),
+        pivot: (51.0, 63.0),
+        hitbox: (0.0, 0.0, 102.0, 126.0),
+    },
+    SpriteInfo {
+        file: "hero_down3.png",
+        atlas_pos: (413, 964),
+        size: (102, 126),
+        pivot: (51.0, 63.0),
+        hitbox: (0.0, 0.0, 102.0, 126.0),
+    },
+    SpriteInfo {
+        file: "hero_down4.png",
+        atlas_pos: (516, 964),
+        size: (102, 126),
+        pivot: (51.0, 63.0),
+        hitbox: (0.0, 0.0, 102.0, 126.0),
+    },
+    SpriteInfo {
+        file: "hitbox.png",
+        atlas_pos: (603, 1187),
+        size: (32, 32),
+        pivot: (16.0, 16.0),
+        hitbox: (0.0, 0.0, 32.0, 32.0),
+    },
+    SpriteInfo {
+        file: "life.png",
+        atlas_pos: (393, 1091),
+        size: (46, 57),
+        pivot: (23.0, 28.5),
+        hitbox: (0.0, 0.0, 46.0, 57.0),
+    },
+    SpriteInfo {
+        file: "pause_nor.png",
+        atlas_pos: (672, 1091),
+        size: (60, 45),
+        pivot: (30.0, 22.5),
+        hitbox: (0.0, 0.0, 60.0, 45.0),
+    },
+    SpriteInfo {
+        file: "pause_pressed.png",
+        atlas_pos: (733, 1091),
+        size: (60, 45),
+        pivot: (30.0, 22.5),
+        hitbox: (0.0, 0.0, 60.0, 45.0),
+    },
+    SpriteInfo {
+        file: "resume_nor.png",
+        atlas_pos: (794, 1091),
+        size: (60, 45),
+        pivot: (30.0, 22.5),
+        hitbox: (0.0, 0.0, 60.0, 45.0),
+    },
+    SpriteInfo {
+        file: "resume_pressed.png",
+        atlas_pos: (855, 1091),
+        size: (60, 45),
+        pivot: (30.0, 22.5),
+        hitbox: (0.0, 0.0, 60.0, 45.0),
+    },
+    SpriteInfo {
+        file: "star.png",
+        atlas_pos: (828, 1187),
+        size: (7, 7),
+        pivot: (3.5, 3.5),
+        hitbox: (0.0, 0.0, 7.0, 7.0),
+    },
+];
@@ -0,0 +1,196 @@
+//! Turning game events into sound and music cues
+//!
+//! The core only decides *what* should be heard and when, the host owns the
+//! actual playback.
+
+use serde::Serialize;
+
+use crate::{entity::enemy::EnemyKind, event::GameEvent};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SoundCue {
+    Shot,
+    EnemyHit,
+    SmallExplosion,
+    BigExplosion,
+    BossExplosion,
+    BossPhase,
+    HeroHit,
+    HeroDeath,
+    Pickup,
+    WaveStart,
+}
+
+impl SoundCue {
+    /// Every cue, in declaration order so that `ALL[cue as usize] == cue`
+    pub const ALL: [SoundCue; 10] = [
+        SoundCue::Shot,
+        SoundCue::EnemyHit,
+        SoundCue::SmallExplosion,
+        SoundCue::BigExplosion,
+        SoundCue::BossExplosion,
+        SoundCue::BossPhase,
+        SoundCue::HeroHit,
+        SoundCue::HeroDeath,
+        SoundCue::Pickup,
+        SoundCue::WaveStart,
+    ];
+    const COUNT: usize = Self::ALL.len();
+
+    /// Higher wins when too many cues compete for the same tick
+    pub fn priority(self) -> u8 {
+        match self {
+            SoundCue::Shot => 0,
+            SoundCue::EnemyHit => 1,
+            SoundCue::SmallExplosion => 2,
+            SoundCue::Pickup => 3,
+            SoundCue::BigExplosion => 4,
+            SoundCue::WaveStart => 4,
+            SoundCue::BossPhase => 5,
+            SoundCue::BossExplosion => 6,
+            SoundCue::HeroHit => 7,
+            SoundCue::HeroDeath => 8,
+        }
+    }
+
+    /// Minimum ticks between two plays of the cue
+    pub fn cooldown(self) -> u32 {
+        match self {
+            SoundCue::Shot => 3,
+            SoundCue::EnemyHit => 2,
+            SoundCue::SmallExplosion => 1,
+            _ => 0,
+        }
+    }
+
+    fn from_event(event: &GameEvent) -> Option<Self> {
+        let cue = match event {
+            GameEvent::BulletFired { .. } => SoundCue::Shot,
+            GameEvent::EnemyHit { .. } => SoundCue::EnemyHit,
+            GameEvent::EnemyKilled { kind, .. } => match kind {
+                EnemyKind::MiddleCup => SoundCue::SmallExplosion,
+                EnemyKind::BigCup => SoundCue::BigExplosion,
+                EnemyKind::SuperBigCup => SoundCue::BossExplosion,
+            },
+            GameEvent::HeroHit { .. } => SoundCue::HeroHit,
+            GameEvent::HeroDied { .. } => SoundCue::HeroDeath,
+            GameEvent::PickupCollected { .. } => SoundCue::Pickup,
+            GameEvent::WaveStarted { .. } => SoundCue::WaveStart,
+            GameEvent::BossPhaseChanged { .. } => SoundCue::BossPhase,
+            GameEvent::NearMiss { .. }
+            | GameEvent::ScoreAwarded { .. }
+            | GameEvent::DirectorAdjusted { .. }
+            | GameEvent::AchievementUnlocked { .. }
+            | GameEvent::AnimationEvent { .. } => return None,
+        };
+        Some(cue)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MusicState {
+    Normal,
+    Boss,
+    GameOver,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AudioCue {
+    Sound {
+        cue: SoundCue,
+    },
+    /// Switch the background music
+    Music {
+        state: MusicState,
+    },
+}
+
+/// At most this many sound cues are queued per tick
+const MAX_SOUNDS_PER_TICK: usize = 4;
+/// Undrained cues beyond this are dropped, oldest first
+const MAX_QUEUED_CUES: usize = 256;
+
+pub struct AudioDirector {
+    /// ticks left before each [`SoundCue`] may play again
+    cooldowns: [u32; SoundCue::COUNT],
+    music: Option<MusicState>,
+    queue: Vec<AudioCue>,
+}
+
+impl Default for AudioDirector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AudioDirector {
+    pub fn new() -> Self {
+        Self {
+            cooldowns: [0; SoundCue::COUNT],
+            music: None,
+            queue: vec![],
+        }
+    }
+
+    /// Queue the cues for the events of one tick, `boss_alive` and `game_over`
+    /// drive the music
+    pub fn tick(&mut self, events: &[GameEvent], boss_alive: bool, game_over: bool) {
+        for cooldown in self.cooldowns.iter_mut() {
+            *cooldown = cooldown.saturating_sub(1);
+        }
+
+        let mut cues: Vec<SoundCue> = events.iter().filter_map(SoundCue::from_event).collect();
+        // stable, so equal priorities keep the event order
+        cues.sort_by_key(|cue| std::cmp::Reverse(cue.priority()));
+        let mut played = 0;
+        for cue in cues {
+            if played == MAX_SOUNDS_PER_TICK {
+                break;
+            }
+            let cooldown = &mut self.cooldowns[cue as usize];
+            if *cooldown > 0 {
+                continue;
+            }
+            *cooldown = cue.cooldown().max(1);
+            self.queue.push(AudioCue::Sound { cue });
+            played += 1;
+        }
+
+        self.update_music(boss_alive, game_over);
+
+        if self.queue.len() > MAX_QUEUED_CUES {
+            let overflow = self.queue.len() - MAX_QUEUED_CUES;
+            self.queue.drain(..overflow);
+        }
+    }
+
+    /// Forget the sounds of a timeline that was left, e.g. by a rewind, and
+    /// switch the music to the one of the new current state
+    pub fn resync(&mut self, boss_alive: bool, game_over: bool) {
+        self.cooldowns = [0; SoundCue::COUNT];
+        self.queue
+            .retain(|cue| matches!(cue, AudioCue::Music { .. }));
+        self.update_music(boss_alive, game_over);
+    }
+
+    fn update_music(&mut self, boss_alive: bool, game_over: bool) {
+        let music = if game_over {
+            MusicState::GameOver
+        } else if boss_alive {
+            MusicState::Boss
+        } else {
+            MusicState::Normal
+        };
+        if self.music != Some(music) {
+            self.music = Some(music);
+            self.queue.push(AudioCue::Music { state: music });
+        }
+    }
+
+    pub fn take_cues(&mut self) -> Vec<AudioCue> {
+        std::mem::take(&mut self.queue)
+    }
+}
@@ -0,0 +1,140 @@
+//! Scrolling background with parallax layers, drawn by the core so that it
+//! moves with the game time: it stands still while paused and follows
+//! rewinds and replays like everything else.
+//!
+//! Every layer is a function of the time only, the procedural ones place
+//! their sprites with a hash of where they are instead of an RNG.
+
+use crate::{
+    render::{BitmapAsset, Primitive},
+    GameSettings,
+};
+
+/// Waves per stage, see [`stage_of_wave`]
+const STAGE_WAVES: u32 = 4;
+/// Height of the bands the procedural layers are cut into, in pixels
+const BAND_HEIGHT: f64 = 128.0;
+/// Extra bands drawn above and below the screen, so sprites don't pop in
+const BAND_MARGIN: f64 = BAND_HEIGHT;
+
+/// The look of the background at some point of the run
+struct Stage {
+    /// Tint of the tiles, 0 for none
+    tile_tint: u32,
+    stars_per_band: u32,
+    clouds_per_band: u32,
+}
+
+const STAGES: [Stage; 3] = [
+    // day
+    Stage {
+        tile_tint: 0,
+        stars_per_band: 1,
+        clouds_per_band: 1,
+    },
+    // dusk
+    Stage {
+        tile_tint: 0xff80_4050,
+        stars_per_band: 3,
+        clouds_per_band: 1,
+    },
+    // night
+    Stage {
+        tile_tint: 0x1020_4090,
+        stars_per_band: 6,
+        clouds_per_band: 0,
+    },
+];
+
+/// Stage of the background during `wave`
+pub fn stage_of_wave(wave: u32) -> usize {
+    ((wave.saturating_sub(1) / STAGE_WAVES) as usize).min(STAGES.len() - 1)
+}
+
+enum Layer {
+    /// The background image, tiled
+    Tiles,
+    Stars,
+    Clouds,
+}
+
+/// Back to front, with the speed of each layer in pixels per tick
+const LAYERS: [(Layer, f64); 3] = [
+    (Layer::Tiles, 0.5),
+    (Layer::Stars, 1.0),
+    (Layer::Clouds, 3.0),
+];
+
+/// SplitMix64, good enough to scatter sprites
+fn hash(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+/// Uniform in `[0, 1)` from 16 bits of `bits`
+fn unit(bits: u64) -> f32 {
+    (bits & 0xffff) as f32 / 65536.0
+}
+
+/// The background at `time`, in ticks with the fraction since the last one,
+/// back to front
+pub fn render(settings: &GameSettings, wave: u32, time: f64) -> Vec<Primitive> {
+    let stage = &STAGES[stage_of_wave(wave)];
+    let (width, height) = (settings.width as f64, settings.height as f64);
+    let mut primitives = vec![];
+    for (index, (layer, speed)) in LAYERS.iter().enumerate() {
+        let scrolled = time * speed;
+        match layer {
+            Layer::Tiles => {
+                let (tile_width, tile_height) = BitmapAsset::Background.info().size;
+                let (tile_width, tile_height) = (tile_width as f64, tile_height as f64);
+                let offset = scrolled % tile_height;
+                let columns = (width / tile_width).ceil() as u32;
+                let rows = (height / tile_height).ceil() as u32 + 1;
+                for row in 0..rows {
+                    for column in 0..columns {
+                        let x = tile_width * (column as f64 + 0.5);
+                        let y = tile_height * (row as f64 + 0.5) - offset;
+                        primitives.push(
+                            Primitive::new(BitmapAsset::Background, (x as f32, y as f32), 0.0)
+                                .with_tint(stage.tile_tint),
+                        );
+                    }
+                }
+            }
+            Layer::Stars | Layer::Clouds => {
+                let (bitmap, count) = match layer {
+                    Layer::Stars => (BitmapAsset::Star, stage.stars_per_band),
+                    _ => (BitmapAsset::Cloud, stage.clouds_per_band),
+                };
+                let first = ((scrolled - BAND_MARGIN) / BAND_HEIGHT).floor() as i64;
+                let last = ((scrolled + height + BAND_MARGIN) / BAND_HEIGHT).floor() as i64;
+                for band in first..=last {
+                    for i in 0..count as u64 {
+                        let bits = hash(((band as u64) << 16) ^ ((index as u64) << 8) ^ i);
+                        let (x, y, size, shade) = (
+                            unit(bits),
+                            unit(bits >> 16),
+                            unit(bits >> 32),
+                            unit(bits >> 48),
+                        );
+                        let x = x as f64 * width;
+                        let y = (band as f64 + y as f64) * BAND_HEIGHT - scrolled;
+                        let (scale, alpha) = match layer {
+                            Layer::Stars => (0.5 + size, 0.3 + 0.5 * shade),
+                            _ => (0.6 + 0.8 * size, 0.15 + 0.2 * shade),
+                        };
+                        primitives.push(
+                            Primitive::new(bitmap, (x as f32, y as f32), 0.0)
+                                .with_scale(scale)
+                                .with_alpha(alpha),
+                        );
+                    }
+                }
+            }
+        }
+    }
+    primitives
+}
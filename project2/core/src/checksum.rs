@@ -0,0 +1,103 @@
+//! Stable hash of the simulation state, to find where two runs diverge
+//!
+//! Positions are quantized so that the hash only covers what matters to the
+//! game, and fields are fed one by one instead of through serde so that
+//! adding a cosmetic field doesn't change every recorded checksum.
+
+use rand::Rng;
+
+use crate::{
+    entity::{bullet::Bullet, enemy::Enemy, MotionState},
+    GameStates,
+};
+
+/// Positions and speeds are hashed in steps of 1/64 pixel
+const QUANTUM: f32 = 64.0;
+
+/// FNV-1a, stable across platforms and versions unlike `DefaultHasher`
+struct StateHasher(u64);
+
+impl StateHasher {
+    fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(0x100_0000_01b3);
+        }
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn quantized(&mut self, value: f32) {
+        self.write(&((value * QUANTUM).round() as i64).to_le_bytes());
+    }
+
+    fn motion(&mut self, motion: &MotionState) {
+        self.quantized(motion.pos.x);
+        self.quantized(motion.pos.y);
+        self.quantized(motion.speed.x);
+        self.quantized(motion.speed.y);
+    }
+
+    fn bullets(&mut self, bullets: &[Bullet]) {
+        self.u32(bullets.len() as u32);
+        for bullet in bullets {
+            self.motion(&bullet.motion_state);
+            self.u32(bullet.owner.map_or(u32::MAX, u32::from));
+            self.u32(bullet.grazed as u32);
+        }
+    }
+}
+
+/// Hash of `states`: the RNG, the score, the entity counts and the state of
+/// every entity, with quantized positions
+pub fn hash_states(states: &GameStates) -> u64 {
+    let mut hasher = StateHasher::new();
+    hasher.u32(states.stats.ticks);
+    hasher.u32(states.score);
+    hasher.u32(states.wave);
+    hasher.u32(states.spawn_cooldown);
+    // what the RNG would draw next stands for its state
+    hasher.u64(states.rng.clone().gen());
+    hasher.quantized(states.director.intensity());
+    for player in 0..states.heroes.len() as u8 {
+        let chain = states.scoring.chain(player);
+        hasher.u32(chain.kills);
+        hasher.u32(chain.timer);
+    }
+
+    hasher.u32(states.heroes.len() as u32);
+    for hero in &states.heroes {
+        hasher.u32(hero.state as u32);
+        hasher.u32(hero.lives as u32);
+        hasher.u32(hero.score);
+        hasher.u32(hero.shooting_cooldown as u32);
+        hasher.motion(&hero.motion_state);
+    }
+
+    hasher.u32(states.enemies.len() as u32);
+    for enemy in &states.enemies {
+        hasher.u32(enemy.kind() as u32);
+        let (health, state, cooldown) = match enemy {
+            Enemy::MiddleCup(enemy) => (enemy.health, enemy.state, enemy.shooting_cooldown),
+            Enemy::BigCup(enemy) => (enemy.health, enemy.state, enemy.shooting_cooldown),
+            Enemy::SuperBigCup(enemy) => (enemy.health, enemy.state, enemy.shooting_cooldown),
+        };
+        hasher.u32(health as u32);
+        hasher.u32(state as u32);
+        hasher.u32(cooldown as u32);
+        hasher.motion(enemy.motion_state());
+    }
+
+    hasher.bullets(&states.hero_bullets);
+    hasher.bullets(&states.enemy_bullets);
+    hasher.0
+}
@@ -0,0 +1,127 @@
+//! Fixed step clock: turns the real time elapsed between frames into ticks,
+//! at an adjustable pace
+//!
+//! Only the number of ticks per real second changes with the time scale, every
+//! tick still advances the simulation by the same fixed step, so a run plays
+//! out the same at any speed.
+
+/// Game time of a tick
+pub const MS_PER_TICK: f64 = 50.0;
+pub const MIN_TIME_SCALE: f32 = 0.1;
+pub const MAX_TIME_SCALE: f32 = 8.0;
+/// A long stall (a hidden tab...) is not caught up beyond that
+const MAX_TICKS_PER_ADVANCE: u32 = 16;
+
+/// Time scale at the deepest of a slow motion
+pub const SLOW_MOTION_SCALE: f32 = 0.25;
+/// Ticks of slow motion after a boss dies
+pub const BOSS_SLOW_MOTION_TICKS: u32 = 40;
+/// Share of a slow motion spent getting back to full speed
+const SLOW_MOTION_RECOVERY: f32 = 0.25;
+
+#[derive(Clone, Copy)]
+struct SlowMotion {
+    remaining: u32,
+    duration: u32,
+}
+
+impl SlowMotion {
+    fn scale(&self) -> f32 {
+        let left = self.remaining as f32 / self.duration as f32;
+        let recovery = (1.0 - left / SLOW_MOTION_RECOVERY).max(0.0);
+        SLOW_MOTION_SCALE + (1.0 - SLOW_MOTION_SCALE) * recovery
+    }
+}
+
+pub struct Clock {
+    /// Set by the player, see [`Clock::effective_scale`] for the one in use
+    scale: f32,
+    slow_motion: Option<SlowMotion>,
+    /// Game time since the last tick, in ms
+    accumulator: f64,
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            slow_motion: None,
+            accumulator: 0.0,
+        }
+    }
+}
+
+impl Clock {
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// Clamped to the allowed range, NaN and infinities are ignored
+    pub fn set_scale(&mut self, scale: f32) {
+        if scale.is_finite() {
+            self.scale = scale.clamp(MIN_TIME_SCALE, MAX_TIME_SCALE);
+        }
+    }
+
+    /// The scale with the slow motion applied
+    pub fn effective_scale(&self) -> f32 {
+        self.scale
+            * self
+                .slow_motion
+                .map_or(1.0, |slow_motion| slow_motion.scale())
+    }
+
+    /// Slow down for `ticks` ticks, getting back to speed towards the end,
+    /// replacing a shorter slow motion in progress
+    pub fn slow_motion(&mut self, ticks: u32) {
+        if ticks == 0 {
+            return;
+        }
+        if self
+            .slow_motion
+            .is_some_and(|slow_motion| slow_motion.remaining >= ticks)
+        {
+            return;
+        }
+        self.slow_motion = Some(SlowMotion {
+            remaining: ticks,
+            duration: ticks,
+        });
+    }
+
+    /// Let `elapsed_ms` of real time pass, returns how many ticks are due,
+    /// NaN and infinities are ignored
+    pub fn advance(&mut self, elapsed_ms: f64) -> u32 {
+        if !elapsed_ms.is_finite() {
+            return 0;
+        }
+        self.accumulator += elapsed_ms.max(0.0) * self.effective_scale() as f64;
+        let due = (self.accumulator / MS_PER_TICK) as u32;
+        if due > MAX_TICKS_PER_ADVANCE {
+            self.accumulator %= MS_PER_TICK;
+            return MAX_TICKS_PER_ADVANCE;
+        }
+        self.accumulator -= due as f64 * MS_PER_TICK;
+        due
+    }
+
+    /// A tick ran
+    pub fn tick(&mut self) {
+        if let Some(slow_motion) = &mut self.slow_motion {
+            slow_motion.remaining -= 1;
+            if slow_motion.remaining == 0 {
+                self.slow_motion = None;
+            }
+        }
+    }
+
+    /// Game time since the last tick, to interpolate the rendering with
+    pub fn ms_delta(&self) -> u128 {
+        self.accumulator as u128
+    }
+
+    /// Start counting from a tick that just happened
+    pub fn reset(&mut self) {
+        self.accumulator = 0.0;
+    }
+}
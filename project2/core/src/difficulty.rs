@@ -0,0 +1,172 @@
+//! Difficulty curve: how fast, how many and how aggressive the enemies are,
+//! given the preset picked in [`GameSettings`](crate::GameSettings), the
+//! score and the time survived
+
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::entity::enemy::EnemyKind;
+
+/// Upper bound of [`Difficulty::speed_scale`], whatever the preset
+pub const MAX_SPEED_FRAC: f32 = 2.0;
+/// Lower bound of [`Difficulty::fire_interval`], in ticks
+pub const MIN_FIRE_INTERVAL: u32 = 10;
+/// Lower bound of [`Difficulty::spawn_interval`], in ticks
+pub const MIN_SPAWN_INTERVAL: u32 = 2;
+/// Upper bound of [`Difficulty::max_enemies`]
+pub const MAX_ENEMIES: u32 = 16;
+
+/// Score that takes the normal curve 63% of the way up, about 30 middle cups
+const SCORE_SCALE: f32 = 3000.0;
+/// Ticks that take the normal curve 63% of the way up, 10 minutes
+const TICK_SCALE: f32 = 12000.0;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DifficultyPreset {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    Lunatic,
+}
+
+impl FromStr for DifficultyPreset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "easy" => Ok(Self::Easy),
+            "normal" => Ok(Self::Normal),
+            "hard" => Ok(Self::Hard),
+            "lunatic" => Ok(Self::Lunatic),
+            _ => Err(format!("unknown difficulty `{s}`")),
+        }
+    }
+}
+
+/// Where a preset starts and ends, the curve goes from one to the other
+struct Profile {
+    /// How fast the curve ramps up, 1 for normal
+    ramp: f32,
+    speed_scale: (f32, f32),
+    fire_interval: (u32, u32),
+    spawn_interval: (u32, u32),
+    max_enemies: (u32, u32),
+    /// Shares of the big and super big cups at the top of the curve
+    big_share: f32,
+    super_big_share: f32,
+}
+
+impl DifficultyPreset {
+    pub const ALL: [DifficultyPreset; 4] = [
+        DifficultyPreset::Easy,
+        DifficultyPreset::Normal,
+        DifficultyPreset::Hard,
+        DifficultyPreset::Lunatic,
+    ];
+
+    fn profile(self) -> Profile {
+        match self {
+            DifficultyPreset::Easy => Profile {
+                ramp: 0.5,
+                speed_scale: (0.8, 1.4),
+                fire_interval: (120, 60),
+                spawn_interval: (14, 8),
+                max_enemies: (1, 6),
+                big_share: 0.3,
+                super_big_share: 0.05,
+            },
+            DifficultyPreset::Normal => Profile {
+                ramp: 1.0,
+                speed_scale: (1.0, 1.7),
+                fire_interval: (100, 40),
+                spawn_interval: (10, 5),
+                max_enemies: (1, 10),
+                big_share: 0.4,
+                super_big_share: 0.1,
+            },
+            DifficultyPreset::Hard => Profile {
+                ramp: 1.5,
+                speed_scale: (1.1, 1.9),
+                fire_interval: (80, 25),
+                spawn_interval: (8, 4),
+                max_enemies: (2, 12),
+                big_share: 0.45,
+                super_big_share: 0.15,
+            },
+            DifficultyPreset::Lunatic => Profile {
+                ramp: 2.5,
+                speed_scale: (1.2, MAX_SPEED_FRAC),
+                fire_interval: (60, MIN_FIRE_INTERVAL),
+                spawn_interval: (6, 3),
+                max_enemies: (3, MAX_ENEMIES),
+                big_share: 0.5,
+                super_big_share: 0.2,
+            },
+        }
+    }
+}
+
+/// The tuning of the game at some point of a run
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Difficulty {
+    /// How far along the curve, from 0 at the start to 1 (never reached)
+    pub level: f32,
+    /// Multiplier of the speed of the spawned enemies
+    pub speed_scale: f32,
+    /// Ticks between two shots of an enemy
+    pub fire_interval: u32,
+    /// Ticks between two spawn waves
+    pub spawn_interval: u32,
+    /// Enemies allowed on screen
+    pub max_enemies: u32,
+    /// Chances of a spawned enemy being a middle, big or super big cup,
+    /// summing to 1
+    pub mix: [f32; 3],
+}
+
+fn lerp(range: (f32, f32), level: f32) -> f32 {
+    range.0 + (range.1 - range.0) * level
+}
+
+fn lerp_ticks(range: (u32, u32), level: f32) -> u32 {
+    lerp((range.0 as f32, range.1 as f32), level).round() as u32
+}
+
+impl Difficulty {
+    /// The difficulty of `preset` once `score` points were made in `ticks` ticks
+    ///
+    /// Every field only gets harder as the score or the ticks go up, and stays
+    /// within the bounds declared in this module.
+    pub fn at(preset: DifficultyPreset, score: u32, ticks: u32) -> Self {
+        let profile = preset.profile();
+        let progress = score as f32 / SCORE_SCALE + ticks as f32 / TICK_SCALE;
+        let level = (1.0 - (-profile.ramp * progress).exp()).clamp(0.0, 1.0);
+
+        let big = profile.big_share * level;
+        let super_big = profile.super_big_share * level;
+        Self {
+            level,
+            speed_scale: lerp(profile.speed_scale, level).min(MAX_SPEED_FRAC),
+            fire_interval: lerp_ticks(profile.fire_interval, level).max(MIN_FIRE_INTERVAL),
+            spawn_interval: lerp_ticks(profile.spawn_interval, level).max(MIN_SPAWN_INTERVAL),
+            max_enemies: lerp_ticks(profile.max_enemies, level).clamp(1, MAX_ENEMIES),
+            mix: [1.0 - big - super_big, big, super_big],
+        }
+    }
+
+    /// The kind of enemy to spawn for `roll`, uniform in `[0, 1)`
+    pub fn pick(&self, roll: f32) -> EnemyKind {
+        if roll < self.mix[0] {
+            EnemyKind::MiddleCup
+        } else if roll < self.mix[0] + self.mix[1] {
+            EnemyKind::BigCup
+        } else {
+            EnemyKind::SuperBigCup
+        }
+    }
+}
@@ -0,0 +1,109 @@
+//! Adaptive director: nudges the difficulty curve up for players who cruise
+//! and down for players who struggle
+//!
+//! It only watches the events of the simulation and lives in
+//! [`GameStates`](crate::GameStates), so a run with the director on is as
+//! deterministic and replayable as one without.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    difficulty::{Difficulty, MAX_ENEMIES, MIN_FIRE_INTERVAL, MIN_SPAWN_INTERVAL},
+    event::GameEvent,
+    game_log,
+};
+
+/// Ticks between two evaluations, 5 seconds
+pub const EVALUATION_INTERVAL: u32 = 100;
+/// Bounds of [`Director::intensity`]
+pub const MAX_INTENSITY: f32 = 1.0;
+/// Most the intensity moves in one evaluation
+const MAX_STEP: f32 = 0.25;
+
+const KILL_WEIGHT: f32 = 0.05;
+const HIT_WEIGHT: f32 = 0.5;
+const NEAR_MISS_WEIGHT: f32 = 0.05;
+/// Pulls an idle player's intensity down a little every evaluation
+const IDLE_DRIFT: f32 = 0.05;
+
+/// How much the spawns and the fire rate move at full intensity, as a fraction
+const SPAWN_NUDGE: f32 = 0.3;
+const AGGRESSION_NUDGE: f32 = 0.4;
+
+/// What the director saw during the current evaluation window
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub struct Performance {
+    pub kills: u32,
+    pub hits_taken: u32,
+    pub near_misses: u32,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Director {
+    /// From `-MAX_INTENSITY`, easier than the curve, to `MAX_INTENSITY`, harder
+    intensity: f32,
+    window: Performance,
+    ticks: u32,
+}
+
+impl Director {
+    pub fn intensity(&self) -> f32 {
+        self.intensity
+    }
+
+    /// Count the kills, hits and near misses of a tick, and every
+    /// [`EVALUATION_INTERVAL`] ticks move the intensity accordingly,
+    /// returning the decision to record in the event stream
+    pub fn observe(&mut self, events: &[GameEvent]) -> Option<GameEvent> {
+        for event in events {
+            match event {
+                GameEvent::EnemyKilled { .. } => self.window.kills += 1,
+                GameEvent::HeroHit { .. } => self.window.hits_taken += 1,
+                GameEvent::NearMiss { .. } => self.window.near_misses += 1,
+                _ => (),
+            }
+        }
+        self.ticks += 1;
+        if !self.ticks.is_multiple_of(EVALUATION_INTERVAL) {
+            return None;
+        }
+
+        let window = std::mem::take(&mut self.window);
+        let step = KILL_WEIGHT * window.kills as f32
+            - HIT_WEIGHT * window.hits_taken as f32
+            - NEAR_MISS_WEIGHT * window.near_misses as f32
+            - IDLE_DRIFT;
+        let intensity =
+            (self.intensity + step.clamp(-MAX_STEP, MAX_STEP)).clamp(-MAX_INTENSITY, MAX_INTENSITY);
+        if intensity == self.intensity {
+            return None;
+        }
+        game_log!(
+            Debug,
+            Director,
+            "intensity {:.2} -> {intensity:.2} after {window:?}",
+            self.intensity
+        );
+        self.intensity = intensity;
+        Some(GameEvent::DirectorAdjusted {
+            intensity,
+            performance: window,
+        })
+    }
+
+    /// `difficulty` nudged by the intensity, still within the bounds of
+    /// [`crate::difficulty`]
+    pub fn adjust(&self, difficulty: Difficulty) -> Difficulty {
+        let spawn = 1.0 + SPAWN_NUDGE * self.intensity;
+        let aggression = 1.0 + AGGRESSION_NUDGE * self.intensity;
+        Difficulty {
+            spawn_interval: ((difficulty.spawn_interval as f32 / spawn).round() as u32)
+                .max(MIN_SPAWN_INTERVAL),
+            max_enemies: ((difficulty.max_enemies as f32 * spawn).round() as u32)
+                .clamp(1, MAX_ENEMIES),
+            fire_interval: ((difficulty.fire_interval as f32 / aggression).round() as u32)
+                .max(MIN_FIRE_INTERVAL),
+            ..difficulty
+        }
+    }
+}
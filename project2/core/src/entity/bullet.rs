@@ -1,29 +1,49 @@
 use nalgebra::Vector2;
+use serde::{Deserialize, Serialize};
 
 use crate::render::{BitmapAsset, Primitive, Render};
 
-use super::{CollisionBox, MotionState};
+use super::{hero::player_tint, CollisionBox, MotionState};
 
-
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub struct Bullet {
     pub motion_state: MotionState,
+    /// Player who fired it, none for enemy bullets
+    pub owner: Option<u8>,
+    /// Whether it already went close to a hero, counted as a near miss
+    #[serde(default)]
+    pub grazed: bool,
 }
 
 impl CollisionBox for Bullet {
     fn bounding_box(&self) -> (f32, f32, f32, f32) {
-        (self.motion_state.pos.x, self.motion_state.pos.y, 5.0, 11.0)
+        let pos = self.motion_state.pos;
+        self.bitmap().info().bounding_box((pos.x, pos.y))
     }
 }
 
 impl Bullet {
-    fn new(x: f32, y: f32, speed_x: f32, speed_y: f32) -> Self {
+    pub fn new(x: f32, y: f32, speed_x: f32, speed_y: f32) -> Self {
         Self {
             motion_state: MotionState {
                 pos: Vector2::new(x, y),
                 speed: Vector2::new(speed_x, speed_y),
                 ..Default::default()
             },
+            owner: None,
+            grazed: false,
+        }
+    }
+
+    pub fn with_owner(mut self, player: u8) -> Self {
+        self.owner = Some(player);
+        self
+    }
+
+    fn bitmap(&self) -> BitmapAsset {
+        match self.owner {
+            Some(_) => BitmapAsset::BulletPlayer,
+            None => BitmapAsset::BulletEnemy,
         }
     }
 }
@@ -33,10 +53,7 @@ impl Render for Bullet {
         let predicted_pos =
             self.motion_state.pos + (self.motion_state.speed / 50.0) * ms_delta as f32;
 
-        Primitive::new(
-            BitmapAsset::BulletPlayer,
-            (predicted_pos.x, predicted_pos.y),
-            0.0,
-        )
+        Primitive::new(self.bitmap(), (predicted_pos.x, predicted_pos.y), 0.0)
+            .with_tint(self.owner.map_or(0, player_tint))
     }
 }
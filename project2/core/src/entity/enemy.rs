@@ -1,21 +1,135 @@
-use nalgebra::Vector2;
+use nalgebra::{Rotation2, Vector2};
+use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 
 use crate::{
-    animation::AnimatedBitmap,
+    animation::Animator,
     attribute::MotionAttribute,
-    render::{BitmapAsset, Primitive, Render},
+    render::{Primitive, Render},
     GameSettings,
 };
 
-use super::{CollisionBox, Entity, EntityState, MotionState};
+use super::{bullet::Bullet, CollisionBox, Entity, EntityState, MotionState};
 
+/// Speed of the enemy bullets, in pixels per tick
+pub const ENEMY_BULLET_SPEED: f32 = 4.0;
+/// Angles of the fan of bullets fired by super big cups, around the aim
+const SUPER_BIG_CUP_SPREAD: [f32; 3] = [-0.25, 0.0, 0.25];
+
+#[derive(Clone, Serialize, Deserialize)]
 pub enum Enemy {
     MiddleCup(Enemy1),
     BigCup(Enemy2),
     SuperBigCup(Enemy3),
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EnemyKind {
+    MiddleCup,
+    BigCup,
+    SuperBigCup,
+}
+
+impl Enemy {
+    pub fn new(kind: EnemyKind, x: f32, y: f32) -> Self {
+        match kind {
+            EnemyKind::MiddleCup => Enemy::MiddleCup(Enemy1::new(x, y)),
+            EnemyKind::BigCup => Enemy::BigCup(Enemy2::new(x, y)),
+            EnemyKind::SuperBigCup => Enemy::SuperBigCup(Enemy3::new(x, y)),
+        }
+    }
+
+    pub fn kind(&self) -> EnemyKind {
+        match self {
+            Enemy::MiddleCup(_) => EnemyKind::MiddleCup,
+            Enemy::BigCup(_) => EnemyKind::BigCup,
+            Enemy::SuperBigCup(_) => EnemyKind::SuperBigCup,
+        }
+    }
+
+    pub fn motion_state(&self) -> &MotionState {
+        match self {
+            Enemy::MiddleCup(enemy) => &enemy.motion_state,
+            Enemy::BigCup(enemy) => &enemy.motion_state,
+            Enemy::SuperBigCup(enemy) => &enemy.motion_state,
+        }
+    }
+
+    pub fn motion_state_mut(&mut self) -> &mut MotionState {
+        match self {
+            Enemy::MiddleCup(enemy) => &mut enemy.motion_state,
+            Enemy::BigCup(enemy) => &mut enemy.motion_state,
+            Enemy::SuperBigCup(enemy) => &mut enemy.motion_state,
+        }
+    }
+
+    /// Count down to the next shot, and when it's time fire, waiting
+    /// `fire_interval` ticks for the next, the bullets go at `bullet_speed`
+    ///
+    /// Middle cups fire straight down, the others aim at `target` if any.
+    pub fn fire(
+        &mut self,
+        target: Option<Vector2<f32>>,
+        fire_interval: u32,
+        bullet_speed: f32,
+    ) -> Vec<Bullet> {
+        let kind = self.kind();
+        let (state, cooldown, pos) = match self {
+            Enemy::MiddleCup(enemy) => (
+                enemy.state,
+                &mut enemy.shooting_cooldown,
+                enemy.motion_state.pos,
+            ),
+            Enemy::BigCup(enemy) => (
+                enemy.state,
+                &mut enemy.shooting_cooldown,
+                enemy.motion_state.pos,
+            ),
+            Enemy::SuperBigCup(enemy) => (
+                enemy.state,
+                &mut enemy.shooting_cooldown,
+                enemy.motion_state.pos,
+            ),
+        };
+        if state == EntityState::DieAnimating || state == EntityState::Died {
+            return vec![];
+        }
+        if *cooldown > 0 {
+            *cooldown -= 1;
+            return vec![];
+        }
+        *cooldown = fire_interval.min(u16::MAX as u32) as u16;
+
+        let aim = target
+            .filter(|_| kind != EnemyKind::MiddleCup)
+            .map(|target| target - pos)
+            .filter(|aim| aim.norm() > 0.0)
+            .map_or(Vector2::new(0.0, -1.0), |aim| aim.normalize());
+        let spread: &[f32] = match kind {
+            EnemyKind::SuperBigCup => &SUPER_BIG_CUP_SPREAD,
+            _ => &[0.0],
+        };
+        spread
+            .iter()
+            .map(|&angle| {
+                let speed = Rotation2::new(angle) * aim * bullet_speed;
+                Bullet::new(pos.x, pos.y, speed.x, speed.y)
+            })
+            .collect()
+    }
+
+    /// Wait `ticks` before the first shot
+    pub fn set_shooting_cooldown(&mut self, ticks: u32) {
+        let ticks = ticks.min(u16::MAX as u32) as u16;
+        match self {
+            Enemy::MiddleCup(enemy) => enemy.shooting_cooldown = ticks,
+            Enemy::BigCup(enemy) => enemy.shooting_cooldown = ticks,
+            Enemy::SuperBigCup(enemy) => enemy.shooting_cooldown = ticks,
+        }
+    }
+}
+
 impl Render for Enemy {
     fn render(&self, ms_delta: u128) -> Primitive {
         match self {
@@ -27,14 +141,17 @@ impl Render for Enemy {
 }
 
 #[wasm_bindgen]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Enemy1 {
     pub health: u16,
     #[wasm_bindgen(skip)]
     pub motion_state: MotionState,
     pub shooting_cooldown: u16,
     pub state: EntityState,
-    normal_animation: AnimatedBitmap,
-    die_animation: AnimatedBitmap,
+    /// Player whose bullet hit it last, credited with the kill
+    #[wasm_bindgen(skip)]
+    pub last_hit_by: Option<u8>,
+    animator: Animator,
 }
 
 impl Enemy1 {
@@ -47,42 +164,41 @@ impl Enemy1 {
                 acc: Vector2::zeros(),
                 acc_val: 0.0,
                 friction: 0.0,
+                max_speed: None,
             },
             shooting_cooldown: 0,
             state: EntityState::Normal,
-            normal_animation: AnimatedBitmap::new(vec![BitmapAsset::Enemy1], 3),
-            die_animation: AnimatedBitmap::new(
-                vec![
-                    BitmapAsset::Enemy1Down1,
-                    BitmapAsset::Enemy1Down2,
-                    BitmapAsset::Enemy1Down3,
-                    BitmapAsset::Enemy1Down4,
-                ],
-                2,
-            ),
+            last_hit_by: None,
+            animator: Animator::new("enemy1"),
         }
     }
 
-    /// The tick method of an entity only handles it self's inner state
-    pub fn tick(&mut self, settings: &GameSettings) {
+    /// The tick method of an entity only handles it self's inner state,
+    /// returns the animation events it went through
+    pub fn tick(&mut self, settings: &GameSettings) -> Vec<&'static str> {
         self.motion_state.tick(settings);
+        self.animator.set_state(self.state);
+        let animation = self.animator.tick();
         match self.state {
-            EntityState::Normal => {
-                self.normal_animation.tick();
+            EntityState::HittedAnimating if animation.finished => {
+                self.state = EntityState::Normal;
             }
-            EntityState::DieAnimating => {
-                if self.die_animation.tick() {
-                    self.state = EntityState::Died;
-                }
+            EntityState::DieAnimating if animation.finished => {
+                self.state = EntityState::Died;
             }
             _ => (),
         }
+        animation.events
     }
 }
 
 impl CollisionBox for Enemy1 {
     fn bounding_box(&self) -> (f32, f32, f32, f32) {
-        (self.motion_state.pos.x, self.motion_state.pos.y, 57.0, 43.0)
+        let pos = self.motion_state.pos;
+        self.animator
+            .hitbox_bitmap()
+            .info()
+            .bounding_box((pos.x, pos.y))
     }
 }
 
@@ -97,26 +213,26 @@ impl Render for Enemy1 {
         let predicted_pos =
             self.motion_state.pos + (self.motion_state.speed / 50.0) * ms_delta as f32;
 
-        let bitmap = match self.state {
-            EntityState::Normal => self.normal_animation.cur_bitmap(),
-            EntityState::DieAnimating => self.die_animation.cur_bitmap(),
-            _ => self.normal_animation.cur_bitmap(),
-        };
-
-        Primitive::new(bitmap, (predicted_pos.x, predicted_pos.y), 0.0)
+        Primitive::new(
+            self.animator.cur_bitmap(),
+            (predicted_pos.x, predicted_pos.y),
+            0.0,
+        )
     }
 }
 
 #[wasm_bindgen]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Enemy2 {
     pub health: u16,
     #[wasm_bindgen(skip)]
     pub motion_state: MotionState,
     pub shooting_cooldown: u16,
     pub state: EntityState,
-    normal_animation: AnimatedBitmap,
-    hitted_animation: AnimatedBitmap,
-    die_animation: AnimatedBitmap,
+    /// Player whose bullet hit it last, credited with the kill
+    #[wasm_bindgen(skip)]
+    pub last_hit_by: Option<u8>,
+    animator: Animator,
 }
 
 impl Enemy2 {
@@ -129,48 +245,41 @@ impl Enemy2 {
                 acc: Vector2::zeros(),
                 acc_val: 0.0,
                 friction: 0.0,
+                max_speed: None,
             },
             shooting_cooldown: 0,
             state: EntityState::Normal,
-            normal_animation: AnimatedBitmap::new(vec![BitmapAsset::Enemy2], 3),
-            hitted_animation: AnimatedBitmap::new(vec![BitmapAsset::Enemy2Hit], 3),
-            die_animation: AnimatedBitmap::new(
-                vec![
-                    BitmapAsset::Enemy2Down1,
-                    BitmapAsset::Enemy2Down2,
-                    BitmapAsset::Enemy2Down3,
-                    BitmapAsset::Enemy2Down4,
-                ],
-                2,
-            ),
+            last_hit_by: None,
+            animator: Animator::new("enemy2"),
         }
     }
 
-    /// The tick method of an entity only handles it self's inner state
-    pub fn tick(&mut self, settings: &GameSettings) {
+    /// The tick method of an entity only handles it self's inner state,
+    /// returns the animation events it went through
+    pub fn tick(&mut self, settings: &GameSettings) -> Vec<&'static str> {
         self.motion_state.tick(settings);
+        self.animator.set_state(self.state);
+        let animation = self.animator.tick();
         match self.state {
-            EntityState::Normal => {
-                self.normal_animation.tick();
+            EntityState::HittedAnimating if animation.finished => {
+                self.state = EntityState::Normal;
             }
-            EntityState::HittedAnimating => {
-                if self.hitted_animation.tick() {
-                    self.state = EntityState::Normal;
-                }
-            }
-            EntityState::DieAnimating => {
-                if self.die_animation.tick() {
-                    self.state = EntityState::Died;
-                }
+            EntityState::DieAnimating if animation.finished => {
+                self.state = EntityState::Died;
             }
             _ => (),
         }
+        animation.events
     }
 }
 
 impl CollisionBox for Enemy2 {
     fn bounding_box(&self) -> (f32, f32, f32, f32) {
-        (self.motion_state.pos.x, self.motion_state.pos.y, 69.0, 99.0)
+        let pos = self.motion_state.pos;
+        self.animator
+            .hitbox_bitmap()
+            .info()
+            .bounding_box((pos.x, pos.y))
     }
 }
 
@@ -185,91 +294,75 @@ impl Render for Enemy2 {
         let predicted_pos =
             self.motion_state.pos + (self.motion_state.speed / 50.0) * ms_delta as f32;
 
-        let bitmap = match self.state {
-            EntityState::Normal => self.normal_animation.cur_bitmap(),
-            EntityState::HittedAnimating => self.hitted_animation.cur_bitmap(),
-            EntityState::DieAnimating => self.die_animation.cur_bitmap(),
-            _ => self.normal_animation.cur_bitmap(),
-        };
-
-        Primitive::new(bitmap, (predicted_pos.x, predicted_pos.y), 0.0)
+        Primitive::new(
+            self.animator.cur_bitmap(),
+            (predicted_pos.x, predicted_pos.y),
+            0.0,
+        )
     }
 }
 
 #[wasm_bindgen]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Enemy3 {
     pub health: u16,
     #[wasm_bindgen(skip)]
     pub motion_state: MotionState,
     pub shooting_cooldown: u16,
     pub state: EntityState,
-    normal_animation: AnimatedBitmap,
-    hitted_animation: AnimatedBitmap,
-    die_animation: AnimatedBitmap,
+    /// Player whose bullet hit it last, credited with the kill
+    #[wasm_bindgen(skip)]
+    pub last_hit_by: Option<u8>,
+    animator: Animator,
 }
 
 impl Enemy3 {
+    pub const MAX_HEALTH: u16 = 10;
+
     pub fn new(x: f32, y: f32) -> Self {
         Self {
-            health: 10,
+            health: Self::MAX_HEALTH,
             motion_state: MotionState {
                 pos: Vector2::new(x, y),
                 speed: Vector2::new(0.0, -1.0),
                 acc: Vector2::zeros(),
                 acc_val: 0.0,
                 friction: 0.0,
+                max_speed: None,
             },
             shooting_cooldown: 0,
             state: EntityState::Normal,
-            normal_animation: AnimatedBitmap::new(
-                vec![BitmapAsset::Enemy3N1, BitmapAsset::Enemy3N2],
-                3,
-            ),
-            hitted_animation: AnimatedBitmap::new(vec![BitmapAsset::Enemy3Hit], 3),
-            die_animation: AnimatedBitmap::new(
-                vec![
-                    BitmapAsset::Enemy3Down1,
-                    BitmapAsset::Enemy3Down2,
-                    BitmapAsset::Enemy3Down3,
-                    BitmapAsset::Enemy3Down4,
-                    BitmapAsset::Enemy3Down5,
-                    BitmapAsset::Enemy3Down6,
-                ],
-                2,
-            ),
+            last_hit_by: None,
+            animator: Animator::new("enemy3"),
         }
     }
 
-    /// The tick method of an entity only handles it self's inner state
-    pub fn tick(&mut self, settings: &GameSettings) {
+    /// The tick method of an entity only handles it self's inner state,
+    /// returns the animation events it went through
+    pub fn tick(&mut self, settings: &GameSettings) -> Vec<&'static str> {
         self.motion_state.tick(settings);
+        self.animator.set_state(self.state);
+        let animation = self.animator.tick();
         match self.state {
-            EntityState::Normal => {
-                self.normal_animation.tick();
-            }
-            EntityState::HittedAnimating => {
-                if self.hitted_animation.tick() {
-                    self.state = EntityState::Normal;
-                }
+            EntityState::HittedAnimating if animation.finished => {
+                self.state = EntityState::Normal;
             }
-            EntityState::DieAnimating => {
-                if self.die_animation.tick() {
-                    self.state = EntityState::Died;
-                }
+            EntityState::DieAnimating if animation.finished => {
+                self.state = EntityState::Died;
             }
             _ => (),
         }
+        animation.events
     }
 }
 
 impl CollisionBox for Enemy3 {
     fn bounding_box(&self) -> (f32, f32, f32, f32) {
-        (
-            self.motion_state.pos.x,
-            self.motion_state.pos.y,
-            169.0,
-            258.0,
-        )
+        let pos = self.motion_state.pos;
+        self.animator
+            .hitbox_bitmap()
+            .info()
+            .bounding_box((pos.x, pos.y))
     }
 }
 
@@ -284,13 +377,10 @@ impl Render for Enemy3 {
         let predicted_pos =
             self.motion_state.pos + (self.motion_state.speed / 50.0) * ms_delta as f32;
 
-        let bitmap = match self.state {
-            EntityState::Normal => self.normal_animation.cur_bitmap(),
-            EntityState::HittedAnimating => self.hitted_animation.cur_bitmap(),
-            EntityState::DieAnimating => self.die_animation.cur_bitmap(),
-            _ => self.normal_animation.cur_bitmap(),
-        };
-
-        Primitive::new(bitmap, (predicted_pos.x, predicted_pos.y), 0.0)
+        Primitive::new(
+            self.animator.cur_bitmap(),
+            (predicted_pos.x, predicted_pos.y),
+            0.0,
+        )
     }
 }
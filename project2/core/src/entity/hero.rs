@@ -1,69 +1,246 @@
 use crate::{
-    animation::AnimatedBitmap,
+    animation::Animator,
     attribute::MotionAttribute,
+    input::{
+        ActionEvent, ActionEventReciever, AnalogInputEvent, AnalogInputEventReciever, PointerEvent,
+        PointerEventReciever, PointerPhase, UserInput,
+    },
     render::{BitmapAsset, Primitive, Render},
-    GameSettings, MotionState, UserInputEvent, UserInputEventReciever,
+    GameSettings,
 };
 use nalgebra::Vector2;
+use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::wasm_bindgen;
 
-use super::{CollisionBox, Entity, EntityState};
+use super::{bullet::Bullet, weapon::Weapon, CollisionBox, Entity, EntityState, MotionState};
+
+/// Fastest the hero moves, in pixel/tick
+pub const MAX_SPEED: f32 = 12.0;
+/// Top speed multiplier while focused, the acceleration is kept so that the
+/// hero still gets past the friction
+pub const FOCUS_SPEED_SCALE: f32 = 0.4;
+/// Weapon spread multiplier while focused
+pub const FOCUS_SPREAD_SCALE: f32 = 0.3;
+/// Ticks a respawned hero can't be hit
+const RESPAWN_INVULNERABLE_TICKS: u16 = 60;
+/// Alpha of an invulnerable hero when it doesn't blink, see [`Hero::render_steady`]
+const STEADY_INVULNERABLE_ALPHA: f32 = 0.6;
+
+/// Tint of each player's ship and bullets, as in [`Primitive::with_tint`]
+const PLAYER_TINTS: [u32; 2] = [0, 0x4080_ff90];
+
+pub fn player_tint(player: u8) -> u32 {
+    PLAYER_TINTS.get(player as usize).copied().unwrap_or(0)
+}
+
+/// The hero keeps its offset to the pointer that grabbed it, so a finger
+/// never hides it
+#[derive(Clone, Copy, Serialize, Deserialize)]
+struct Drag {
+    offset: Vector2<f32>,
+    target: Vector2<f32>,
+}
 
 #[wasm_bindgen]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Hero {
+    /// Index of the player controlling it
+    pub player: u8,
     pub health: u16,
+    /// Lives left, including the current one
+    pub lives: u8,
+    /// Score earned by this player
+    pub score: u32,
     #[wasm_bindgen(skip)]
     pub motion_state: MotionState,
-    pub shooting: bool,
+    #[wasm_bindgen(skip)]
+    pub input: UserInput,
+    #[wasm_bindgen(skip)]
+    pub weapon: Weapon,
     pub shooting_cooldown: u16,
     pub state: EntityState,
-    animation: AnimatedBitmap,
-    die_animation: AnimatedBitmap,
+    animator: Animator,
+    drag: Option<Drag>,
+    spawn: Vector2<f32>,
+    invulnerable: u16,
+    /// Multiplier of the hitbox, see [`crate::accessibility::Accessibility`]
+    #[serde(default = "unscaled")]
+    hitbox_scale: f32,
+}
+
+fn unscaled() -> f32 {
+    1.0
 }
 
 impl Hero {
-    pub fn new(x: f32, y: f32) -> Self {
+    pub fn new(player: u8, x: f32, y: f32, lives: u8) -> Self {
         Self {
+            player,
             health: 100,
+            lives,
+            score: 0,
             motion_state: MotionState {
                 pos: Vector2::new(x, y),
                 speed: Vector2::zeros(),
                 acc: Vector2::zeros(),
                 acc_val: 4.0,
                 friction: 1.6,
+                max_speed: Some(MAX_SPEED),
             },
-            shooting: false,
+            input: UserInput::default(),
+            weapon: Weapon::default(),
             shooting_cooldown: 0,
             state: EntityState::Normal,
-            animation: AnimatedBitmap::new(vec![BitmapAsset::Hero1, BitmapAsset::Hero2], 3),
-            die_animation: AnimatedBitmap::new(
-                vec![
-                    BitmapAsset::HeroDown1,
-                    BitmapAsset::HeroDown2,
-                    BitmapAsset::HeroDown3,
-                    BitmapAsset::HeroDown4,
-                ],
-                2,
-            ),
+            animator: Animator::new("hero"),
+            drag: None,
+            spawn: Vector2::new(x, y),
+            invulnerable: 0,
+            hitbox_scale: 1.0,
         }
     }
 
-    /// The tick method of an entity only handles it self's inner state
-    pub fn tick(&mut self, settings: &GameSettings) {
-        if self.state == EntityState::Normal {
-            self.motion_state.tick(settings);
+    pub fn with_hitbox_scale(mut self, scale: f32) -> Self {
+        self.hitbox_scale = scale;
+        self
+    }
+
+    /// Whether the hero can be hit
+    pub fn vulnerable(&self) -> bool {
+        self.state == EntityState::Normal && self.invulnerable == 0
+    }
+
+    /// Lose a life, returns false if the hero couldn't be hit
+    pub fn hit(&mut self) -> bool {
+        if !self.vulnerable() {
+            return false;
         }
-        match self.state {
-            EntityState::Normal => {
-                self.animation.tick();
-            }
-            EntityState::DieAnimating => {
-                if self.die_animation.tick() {
-                    self.state = EntityState::Died;
+        self.lives = self.lives.saturating_sub(1);
+        self.state = EntityState::DieAnimating;
+        self.drag = None;
+        true
+    }
+
+    /// No life left and the last death animation is over
+    pub fn out(&self) -> bool {
+        self.state == EntityState::Died
+    }
+
+    fn respawn(&mut self) {
+        self.state = EntityState::Normal;
+        self.motion_state.pos = self.spawn;
+        self.motion_state.speed = Vector2::zeros();
+        self.invulnerable = RESPAWN_INVULNERABLE_TICKS;
+    }
+
+    /// Shooting key held, or pointer pressed
+    pub fn firing(&self) -> bool {
+        self.input.fire || self.drag.is_some()
+    }
+
+    /// Focus held: slower, tighter shots and a visible hitbox
+    pub fn focused(&self) -> bool {
+        self.input.focus
+    }
+
+    /// Multiplier of the top speed
+    pub fn speed_scale(&self) -> f32 {
+        if self.focused() {
+            FOCUS_SPEED_SCALE
+        } else {
+            1.0
+        }
+    }
+
+    /// The bullets of one shot
+    pub fn shoot(&self) -> impl Iterator<Item = Bullet> + '_ {
+        let pos = self.motion_state.pos;
+        let spread_scale = if self.focused() {
+            FOCUS_SPREAD_SCALE
+        } else {
+            1.0
+        };
+        self.weapon
+            .fire((pos.x, pos.y), spread_scale)
+            .map(|bullet| bullet.with_owner(self.player))
+    }
+
+    /// Marker of the true hitbox, shown while focused
+    pub fn hitbox_indicator(&self, ms_delta: u128) -> Option<Primitive> {
+        if !self.focused() || self.state != EntityState::Normal {
+            return None;
+        }
+        let predicted_offset = (self.motion_state.speed / 50.0) * ms_delta as f32;
+        let (x, y, width, _) = self.bounding_box();
+        let scale = width / BitmapAsset::Hitbox.info().size.0 as f32;
+        Some(
+            Primitive::new(
+                BitmapAsset::Hitbox,
+                (x + predicted_offset.x, y + predicted_offset.y),
+                0.0,
+            )
+            .with_scale(scale),
+        )
+    }
+
+    /// Like [`Render::render`], but faded instead of blinking while
+    /// invulnerable, for when the screen effects are off
+    pub fn render_steady(&self, ms_delta: u128) -> Primitive {
+        let primitive = self.render(ms_delta);
+        if self.invulnerable > 0 {
+            primitive.with_alpha(STEADY_INVULNERABLE_ALPHA)
+        } else {
+            primitive
+        }
+    }
+
+    /// Move straight to the drag target, keyboard acceleration is ignored meanwhile
+    fn follow(&mut self, target: Vector2<f32>, settings: &GameSettings) {
+        let max_speed = MAX_SPEED * self.speed_scale();
+        let motion = &mut self.motion_state;
+        let delta = target - motion.pos;
+        let distance = delta.norm();
+        motion.speed = if distance > max_speed {
+            delta * (max_speed / distance)
+        } else {
+            delta
+        };
+        motion.pos += motion.speed;
+        motion.pos.x = motion.pos.x.clamp(0.0, settings.width as f32);
+        motion.pos.y = motion.pos.y.clamp(0.0, settings.height as f32);
+    }
+
+    /// The tick method of an entity only handles it self's inner state,
+    /// returns the animation events it went through
+    pub fn tick(&mut self, settings: &GameSettings) -> Vec<&'static str> {
+        if self.state == EntityState::Normal {
+            match self.drag {
+                Some(drag) => self.follow(drag.target, settings),
+                None => {
+                    // a half tilted stick accelerates half as much, up to half the speed
+                    let direction = self.input.direction();
+                    let deflection = direction.norm();
+                    self.motion_state.acc = direction * self.motion_state.acc_val;
+                    let max_speed = MAX_SPEED * self.speed_scale();
+                    self.motion_state.max_speed = Some(if deflection > 0.0 {
+                        max_speed * deflection
+                    } else {
+                        max_speed
+                    });
+                    self.motion_state.tick(settings);
                 }
             }
-            _ => (),
         }
+        self.animator.set_state(self.state);
+        let animation = self.animator.tick();
+        self.invulnerable = self.invulnerable.saturating_sub(1);
+        if self.state == EntityState::DieAnimating && animation.finished {
+            if self.lives > 0 {
+                self.respawn();
+            } else {
+                self.state = EntityState::Died;
+            }
+        }
+        animation.events
     }
 }
 
@@ -78,57 +255,49 @@ impl Entity for Hero {
 
 impl CollisionBox for Hero {
     fn bounding_box(&self) -> (f32, f32, f32, f32) {
-        (
-            self.motion_state.pos.x,
-            self.motion_state.pos.y,
-            102.0,
-            126.0,
-        )
+        let pos = self.motion_state.pos;
+        let (x, y, width, height) = self
+            .animator
+            .hitbox_bitmap()
+            .info()
+            .bounding_box((pos.x, pos.y));
+        (x, y, width * self.hitbox_scale, height * self.hitbox_scale)
     }
 }
 
-impl UserInputEventReciever for Hero {
-    fn update(&mut self, user_input_event: &UserInputEvent) {
-        // self.motion_state.update(user_input_event);
+impl ActionEventReciever for Hero {
+    fn update(&mut self, action_event: &ActionEvent) {
+        self.input.update(action_event);
+    }
+}
 
-        match user_input_event.key().as_str() {
-            "w" => {
-                if user_input_event.pressed {
-                    self.motion_state.acc.y = self.motion_state.acc_val;
-                } else {
-                    self.motion_state.acc.y = 0.0;
-                }
-            }
-            "a" => {
-                if user_input_event.pressed {
-                    self.motion_state.acc.x = -self.motion_state.acc_val;
-                } else {
-                    self.motion_state.acc.x = 0.0;
-                }
+impl AnalogInputEventReciever for Hero {
+    fn update_analog(&mut self, analog_event: &AnalogInputEvent) {
+        self.input.stick = Vector2::new(analog_event.x, analog_event.y);
+    }
+}
+
+impl PointerEventReciever for Hero {
+    fn update_pointer(&mut self, pointer_event: &PointerEvent) {
+        let pointer = Vector2::new(pointer_event.x, pointer_event.y);
+        match pointer_event.phase {
+            PointerPhase::Down => {
+                self.drag = Some(Drag {
+                    offset: self.motion_state.pos - pointer,
+                    target: self.motion_state.pos,
+                });
             }
-            "s" => {
-                if user_input_event.pressed {
-                    self.motion_state.acc.y = -self.motion_state.acc_val;
-                } else {
-                    self.motion_state.acc.y = 0.0;
+            PointerPhase::Move => {
+                if let Some(drag) = &mut self.drag {
+                    drag.target = pointer + drag.offset;
                 }
             }
-            "d" => {
-                if user_input_event.pressed {
-                    self.motion_state.acc.x = self.motion_state.acc_val;
-                } else {
-                    self.motion_state.acc.x = 0.0;
+            PointerPhase::Up => {
+                // stop where the pointer left the hero instead of gliding on
+                if self.drag.take().is_some() {
+                    self.motion_state.speed = Vector2::zeros();
                 }
             }
-            _ => (),
-        }
-
-        if user_input_event.key().as_str() == " " {
-            if user_input_event.pressed {
-                self.shooting = true;
-            } else {
-                self.shooting = false;
-            }
         }
     }
 }
@@ -138,13 +307,19 @@ impl Render for Hero {
         let predicted_pos =
             self.motion_state.pos + (self.motion_state.speed / 50.0) * ms_delta as f32;
 
-        let bitmap = match self.state {
-            EntityState::Normal => self.animation.cur_bitmap(),
-            EntityState::DieAnimating => self.die_animation.cur_bitmap(),
-            _ => self.animation.cur_bitmap(),
+        // blink while invulnerable
+        let alpha = if self.invulnerable / 4 % 2 == 1 {
+            0.3
+        } else {
+            1.0
         };
-
-        Primitive::new(bitmap, (predicted_pos.x, predicted_pos.y), 0.0)
+        Primitive::new(
+            self.animator.cur_bitmap(),
+            (predicted_pos.x, predicted_pos.y),
+            0.0,
+        )
+        .with_alpha(alpha)
+        .with_tint(player_tint(self.player))
     }
 }
 
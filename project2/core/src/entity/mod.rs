@@ -1,13 +1,16 @@
 use wasm_bindgen::prelude::*;
 use crate::{attribute::MotionAttribute, GameSettings};
 use nalgebra::Vector2;
+use serde::{Deserialize, Serialize};
 
 pub mod bullet;
 pub mod enemy;
 pub mod hero;
+pub mod weapon;
 
 #[wasm_bindgen]
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum EntityState {
     Normal,
     HittedAnimating,
@@ -20,23 +23,26 @@ pub trait CollisionBox {
     fn bounding_box(&self) -> (f32, f32, f32, f32);
 }
 
+/// Whether the boxes of `a` and `b` are at most `margin` apart
+pub fn within<A: CollisionBox, B: CollisionBox>(a: &A, b: &B, margin: f32) -> bool {
+    (a.bounding_box().0 - b.bounding_box().0).abs()
+        <= (a.bounding_box().2 + b.bounding_box().2) / 2.0 + margin
+        && (a.bounding_box().1 - b.bounding_box().1).abs()
+            <= (a.bounding_box().3 + b.bounding_box().3) / 2.0 + margin
+}
+
 pub fn collisioned<A: CollisionBox, B: CollisionBox>(a: &A, b: &B) -> bool {
-    if (a.bounding_box().0 - b.bounding_box().0).abs()
+    (a.bounding_box().0 - b.bounding_box().0).abs()
         <= (a.bounding_box().2 + b.bounding_box().2) / 2.0
         && (a.bounding_box().1 - b.bounding_box().1).abs()
             <= (a.bounding_box().3 + b.bounding_box().3) / 2.0
-    {
-        true
-    } else {
-        false
-    }
 }
 
 pub trait Entity {
     fn motion_attribute(&self) -> MotionAttribute;
 }
 
-#[derive(Default, Clone, Copy)]
+#[derive(Default, Clone, Copy, Serialize, Deserialize)]
 pub struct MotionState {
     pub pos: Vector2<f32>,
     pub speed: Vector2<f32>,
@@ -44,6 +50,8 @@ pub struct MotionState {
     // constant
     pub acc_val: f32,
     pub friction: f32,
+    /// Speed is clamped to this length after acceleration
+    pub max_speed: Option<f32>,
 }
 
 impl MotionState {
@@ -52,6 +60,14 @@ impl MotionState {
             self.speed.x = self.speed.x.signum() * (self.speed.x.abs() - self.friction).max(0.0);
         }
         self.speed.x += self.acc.x;
+        if self.speed.y.abs() > 0.0 {
+            self.speed.y = self.speed.y.signum() * (self.speed.y.abs() - self.friction).max(0.0);
+        }
+        self.speed.y += self.acc.y;
+        if let Some(max_speed) = self.max_speed {
+            self.speed = self.speed.cap_magnitude(max_speed);
+        }
+
         self.pos.x += self.speed.x;
         if self.pos.x < 0.0 || self.pos.x > game_setting.width as f32 {
             self.pos.x = self.pos.x.clamp(0.0, game_setting.width as f32);
@@ -60,10 +76,6 @@ impl MotionState {
 
         // TODO: better border handling
 
-        if self.speed.y.abs() > 0.0 {
-            self.speed.y = self.speed.y.signum() * (self.speed.y.abs() - self.friction).max(0.0);
-        }
-        self.speed.y += self.acc.y;
         self.pos.y += self.speed.y;
         if self.pos.y < 0.0 || self.pos.y > game_setting.height as f32 {
             self.pos.y = self.pos.y.clamp(0.0, game_setting.height as f32);
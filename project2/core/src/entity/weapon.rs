@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+
+use super::bullet::Bullet;
+
+/// What comes out of a ship when it fires
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct Weapon {
+    /// Bullets per shot, fanned out over `spread`
+    pub streams: u32,
+    /// Angle between the outermost streams, in rad
+    pub spread: f32,
+    /// in pixel/tick
+    pub bullet_speed: f32,
+    /// Ticks between two shots
+    pub cooldown: u16,
+}
+
+impl Default for Weapon {
+    fn default() -> Self {
+        Self {
+            streams: 1,
+            spread: 0.35,
+            bullet_speed: 8.0,
+            cooldown: 10,
+        }
+    }
+}
+
+impl Weapon {
+    /// The bullets of one shot from `pos`, going up, with the spread scaled by `spread_scale`
+    pub fn fire(&self, pos: (f32, f32), spread_scale: f32) -> impl Iterator<Item = Bullet> + '_ {
+        let spread = self.spread * spread_scale;
+        (0..self.streams).map(move |i| {
+            let angle = if self.streams == 1 {
+                0.0
+            } else {
+                -spread / 2.0 + spread * i as f32 / (self.streams - 1) as f32
+            };
+            Bullet::new(
+                pos.0,
+                pos.1,
+                angle.sin() * self.bullet_speed,
+                angle.cos() * self.bullet_speed,
+            )
+        })
+    }
+}
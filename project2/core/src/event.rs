@@ -0,0 +1,80 @@
+use serde::Serialize;
+
+use crate::{director::Performance, entity::enemy::EnemyKind, scoring::ScoreReason};
+
+/// Something that happened during a tick, for the host to react to
+/// (sounds, screen shake, analytics...) without the core knowing about it.
+///
+/// Positions are `(x, y)` in play area coordinates.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize)]
+#[serde(tag = "type")]
+pub enum GameEvent {
+    /// An enemy took a bullet
+    EnemyHit {
+        kind: EnemyKind,
+        pos: (f32, f32),
+    },
+    /// An enemy ran out of health, `score` is what it is worth to `player`,
+    /// the owner of the last bullet it took, chain multiplier included
+    EnemyKilled {
+        kind: EnemyKind,
+        pos: (f32, f32),
+        score: u32,
+        player: Option<u8>,
+    },
+    /// A hero lost a life
+    HeroHit {
+        player: u8,
+        pos: (f32, f32),
+    },
+    /// An enemy bullet went close to a vulnerable hero without hitting it,
+    /// once per bullet
+    NearMiss {
+        player: u8,
+        pos: (f32, f32),
+    },
+    /// The last death animation of a hero is over
+    HeroDied {
+        player: u8,
+        pos: (f32, f32),
+    },
+    BulletFired {
+        player: u8,
+        pos: (f32, f32),
+    },
+    /// Reserved until pickups exist, nothing emits it yet
+    PickupCollected {
+        pos: (f32, f32),
+    },
+    /// The allowed enemy count went up, `wave` is the new count
+    WaveStarted {
+        wave: u32,
+    },
+    /// A super big cup lost half of its health
+    BossPhaseChanged {
+        phase: u8,
+    },
+    /// `player` earned `points`, see [`crate::scoring`]
+    ScoreAwarded {
+        player: u8,
+        points: u32,
+        reason: ScoreReason,
+        pos: (f32, f32),
+    },
+    /// The adaptive director moved its intensity after watching `performance`
+    /// for [`EVALUATION_INTERVAL`](crate::director::EVALUATION_INTERVAL) ticks
+    DirectorAdjusted {
+        intensity: f32,
+        performance: Performance,
+    },
+    /// A goal of [`ACHIEVEMENTS`](crate::achievement::ACHIEVEMENTS) was
+    /// reached for the first time
+    AchievementUnlocked {
+        id: &'static str,
+    },
+    /// A frame carrying an event was entered, see [`Frame::event`](crate::animation::Frame::event)
+    AnimationEvent {
+        name: &'static str,
+        pos: (f32, f32),
+    },
+}
@@ -0,0 +1,72 @@
+//! Running the game without any rendering, as fast as possible
+
+use serde::Serialize;
+
+use crate::{
+    replay::ReplayInput,
+    stats::{Kills, RunStats},
+    Game, GameSettings,
+};
+
+/// What a headless run reports once it is over
+#[derive(Serialize)]
+pub struct RunSummary {
+    pub seed: u64,
+    pub score: u32,
+    pub ticks_survived: u32,
+    pub died: bool,
+    pub bullets_fired: u32,
+    pub accuracy: f32,
+    pub hits_taken: u32,
+    pub kills: Kills,
+    pub max_combo: u32,
+    pub grazes: u32,
+}
+
+impl RunSummary {
+    fn new(seed: u64, score: u32, died: bool, stats: &RunStats) -> Self {
+        Self {
+            seed,
+            score,
+            ticks_survived: stats.ticks,
+            died,
+            bullets_fired: stats.bullets_fired,
+            accuracy: stats.accuracy(),
+            hits_taken: stats.hits_taken,
+            kills: stats.kills,
+            max_combo: stats.max_combo,
+            grazes: stats.grazes,
+        }
+    }
+}
+
+/// Simulate `max_ticks` ticks, or until the hero dies, feeding `inputs` in order.
+///
+/// `inputs` is expected to be sorted by tick.
+pub fn simulate(settings: GameSettings, inputs: &[ReplayInput], max_ticks: u32) -> RunSummary {
+    let seed = settings.seed;
+    let mut game = Game::new(settings);
+    run(&mut game, inputs, max_ticks, |_| ());
+
+    RunSummary::new(seed, game.score(), game.end(), &game.states.stats)
+}
+
+/// Drive `game` like [`simulate`] does, calling `after_tick` after every tick
+pub fn run(
+    game: &mut Game,
+    inputs: &[ReplayInput],
+    max_ticks: u32,
+    mut after_tick: impl FnMut(&mut Game),
+) {
+    let mut inputs = inputs.iter().peekable();
+    for tick in 0..max_ticks {
+        if game.end() {
+            break;
+        }
+        while let Some(input) = inputs.next_if(|input| input.tick <= tick) {
+            input.apply(game);
+        }
+        game.tick();
+        after_tick(game);
+    }
+}
@@ -1,13 +1,135 @@
-use wasm_bindgen::prelude::wasm_bindgen;
+use std::collections::BTreeMap;
 
+use nalgebra::Vector2;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::wasm_bindgen;
 
-#[wasm_bindgen]
+/// Every action currently held, movement is derived from it every tick.
+///
+/// When both directions of an axis are held, the last pressed one wins.
+#[derive(Clone, Copy, Default, PartialEq, Debug, Serialize, Deserialize)]
 pub struct UserInput {
-    pub w: bool,
-    pub a: bool,
-    pub s: bool,
-    pub d: bool,
-    pub space: bool,
+    pub up: bool,
+    pub down: bool,
+    pub left: bool,
+    pub right: bool,
+    pub fire: bool,
+    pub bomb: bool,
+    pub focus: bool,
+    /// Analog stick, already through the deadzone and response curve, used
+    /// when no direction is held
+    pub stick: Vector2<f32>,
+    /// sign of the last pressed direction of each axis
+    last_x: f32,
+    last_y: f32,
+}
+
+impl UserInput {
+    pub fn update(&mut self, action_event: &ActionEvent) {
+        let pressed = action_event.pressed;
+        match action_event.action {
+            Action::MoveUp => self.up = pressed,
+            Action::MoveDown => self.down = pressed,
+            Action::MoveLeft => self.left = pressed,
+            Action::MoveRight => self.right = pressed,
+            Action::Fire => self.fire = pressed,
+            Action::Bomb => self.bomb = pressed,
+            Action::Focus => self.focus = pressed,
+            Action::Pause | Action::Rewind => (),
+        }
+        if pressed {
+            match action_event.action {
+                Action::MoveUp => self.last_y = 1.0,
+                Action::MoveDown => self.last_y = -1.0,
+                Action::MoveLeft => self.last_x = -1.0,
+                Action::MoveRight => self.last_x = 1.0,
+                _ => (),
+            }
+        }
+    }
+
+    fn axis(negative: bool, positive: bool, last: f32) -> f32 {
+        match (negative, positive) {
+            (true, true) => last,
+            (true, false) => -1.0,
+            (false, true) => 1.0,
+            (false, false) => 0.0,
+        }
+    }
+
+    /// The action events that turn `self` into `target`, the stick aside:
+    /// releases first, and the direction that wins an axis pressed last
+    pub fn changes_to(&self, target: &UserInput) -> Vec<ActionEvent> {
+        let held = |input: &UserInput| {
+            [
+                (Action::MoveUp, input.up),
+                (Action::MoveDown, input.down),
+                (Action::MoveLeft, input.left),
+                (Action::MoveRight, input.right),
+                (Action::Fire, input.fire),
+                (Action::Bomb, input.bomb),
+                (Action::Focus, input.focus),
+            ]
+        };
+        let mut events: Vec<ActionEvent> = held(self)
+            .into_iter()
+            .zip(held(target))
+            .filter(|((_, from), (_, to))| from != to)
+            .map(|(_, (action, pressed))| ActionEvent { action, pressed })
+            .collect();
+        events.sort_by_key(|event| event.pressed);
+
+        let mut input = *self;
+        for event in &events {
+            input.update(event);
+        }
+        for (both, last, target_last, negative, positive) in [
+            (
+                target.left && target.right,
+                input.last_x,
+                target.last_x,
+                Action::MoveLeft,
+                Action::MoveRight,
+            ),
+            (
+                target.down && target.up,
+                input.last_y,
+                target.last_y,
+                Action::MoveDown,
+                Action::MoveUp,
+            ),
+        ] {
+            if both && last != target_last {
+                let action = if target_last < 0.0 {
+                    negative
+                } else {
+                    positive
+                };
+                events.push(ActionEvent {
+                    action,
+                    pressed: false,
+                });
+                events.push(ActionEvent {
+                    action,
+                    pressed: true,
+                });
+            }
+        }
+        events
+    }
+
+    /// Unit vector of the held direction (y up), or the stick deflection
+    pub fn direction(&self) -> Vector2<f32> {
+        let direction = Vector2::new(
+            Self::axis(self.left, self.right, self.last_x),
+            Self::axis(self.down, self.up, self.last_y),
+        );
+        if direction == Vector2::zeros() {
+            self.stick.cap_magnitude(1.0)
+        } else {
+            direction.normalize()
+        }
+    }
 }
 
 #[wasm_bindgen]
@@ -28,6 +150,296 @@ impl UserInputEvent {
     }
 }
 
-pub trait UserInputEventReciever {
-    fn update(&mut self, user_input_event: &UserInputEvent);
-}
\ No newline at end of file
+/// What a key, button or pointer means to the game
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Fire,
+    /// Reserved until bombs exist, no key is bound to it by default
+    Bomb,
+    Focus,
+    Pause,
+    /// Handled by the host, see [`Game::rewind`](crate::Game::rewind)
+    Rewind,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ActionEvent {
+    pub action: Action,
+    pub pressed: bool,
+}
+
+pub trait ActionEventReciever {
+    fn update(&mut self, action_event: &ActionEvent);
+}
+
+/// Translates raw keys (`KeyboardEvent.key` values) into [`Action`]s.
+///
+/// Gamepad buttons are keys named `Gamepad<index>`, with the indices of the
+/// standard Gamepad API mapping: 0 is A, 1 is B, 9 is start, 12-15 the d-pad.
+///
+/// An action can have any number of keys and a key can trigger several
+/// actions. Single character keys are matched case-insensitively, so that
+/// holding shift doesn't turn `w` into another key.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct InputMap {
+    bindings: BTreeMap<Action, Vec<String>>,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        let mut map = Self {
+            bindings: BTreeMap::new(),
+        };
+        for (action, keys) in [
+            (Action::MoveUp, ["w", "ArrowUp", "Gamepad12"].as_slice()),
+            (Action::MoveDown, &["s", "ArrowDown", "Gamepad13"]),
+            (Action::MoveLeft, &["a", "ArrowLeft", "Gamepad14"]),
+            (Action::MoveRight, &["d", "ArrowRight", "Gamepad15"]),
+            (Action::Fire, &[" ", "j", "Gamepad0", "Gamepad7"]),
+            (Action::Focus, &["Shift", "Gamepad4", "Gamepad6"]),
+            (Action::Pause, &["Escape", "p", "Gamepad9"]),
+            (Action::Rewind, &["Backspace", "r", "Gamepad8"]),
+        ] {
+            for key in keys {
+                map.bind(action, key);
+            }
+        }
+        map
+    }
+}
+
+impl InputMap {
+    /// Default map of `player` when `players` share the keyboard: the first
+    /// player keeps wasd, the second one gets the arrows, each has the buttons
+    /// of their own gamepad
+    pub fn for_player(player: u8, players: u8) -> Self {
+        let mut map = Self::default();
+        if players < 2 {
+            return map;
+        }
+        if player == 0 {
+            map.unbind(Action::MoveUp, "ArrowUp");
+            map.unbind(Action::MoveDown, "ArrowDown");
+            map.unbind(Action::MoveLeft, "ArrowLeft");
+            map.unbind(Action::MoveRight, "ArrowRight");
+            return map;
+        }
+        map.bindings.clear();
+        for (action, keys) in [
+            (Action::MoveUp, ["ArrowUp", "Gamepad12"].as_slice()),
+            (Action::MoveDown, &["ArrowDown", "Gamepad13"]),
+            (Action::MoveLeft, &["ArrowLeft", "Gamepad14"]),
+            (Action::MoveRight, &["ArrowRight", "Gamepad15"]),
+            (Action::Fire, &["Enter", "0", "Gamepad0", "Gamepad7"]),
+            (Action::Focus, &["/", "Gamepad4", "Gamepad6"]),
+        ] {
+            for key in keys {
+                map.bind(action, key);
+            }
+        }
+        map
+    }
+
+    fn normalize(key: &str) -> String {
+        if key.chars().count() == 1 {
+            key.to_lowercase()
+        } else {
+            key.to_string()
+        }
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let map: Self =
+            serde_json::from_str(json).map_err(|err| format!("invalid input map: {err}"))?;
+        // normalize hand written maps too
+        let mut normalized = Self {
+            bindings: BTreeMap::new(),
+        };
+        for (action, keys) in map.bindings {
+            for key in keys {
+                normalized.bind(action, &key);
+            }
+        }
+        Ok(normalized)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    pub fn bind(&mut self, action: Action, key: &str) {
+        let key = Self::normalize(key);
+        let keys = self.bindings.entry(action).or_default();
+        if !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+
+    pub fn unbind(&mut self, action: Action, key: &str) {
+        let key = Self::normalize(key);
+        if let Some(keys) = self.bindings.get_mut(&action) {
+            keys.retain(|bound| *bound != key);
+        }
+    }
+
+    pub fn keys(&self, action: Action) -> &[String] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Every action bound to `key`
+    pub fn actions<'a>(&'a self, key: &str) -> impl Iterator<Item = Action> + 'a {
+        let key = Self::normalize(key);
+        self.bindings
+            .iter()
+            .filter(move |(_, keys)| keys.contains(&key))
+            .map(|(action, _)| *action)
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PointerPhase {
+    Down,
+    Move,
+    Up,
+}
+
+/// A mouse or touch event, in play area coordinates (origin at the bottom left, y up)
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct PointerEvent {
+    pub phase: PointerPhase,
+    pub x: f32,
+    pub y: f32,
+}
+
+#[wasm_bindgen]
+impl PointerEvent {
+    #[wasm_bindgen(constructor)]
+    pub fn new(phase: PointerPhase, x: f32, y: f32) -> Self {
+        Self { phase, x, y }
+    }
+}
+
+pub trait PointerEventReciever {
+    fn update_pointer(&mut self, pointer_event: &PointerEvent);
+}
+
+/// An analog stick, `x` and `y` in [-1, 1] with y going up
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct AnalogInputEvent {
+    pub x: f32,
+    pub y: f32,
+}
+
+#[wasm_bindgen]
+impl AnalogInputEvent {
+    #[wasm_bindgen(constructor)]
+    pub fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+}
+
+impl AnalogInputEvent {
+    /// Each axis within `[-1, 1]`, a broken axis reads as centered
+    pub fn sanitized(&self) -> Self {
+        let axis = |value: f32| {
+            if value.is_finite() {
+                value.clamp(-1.0, 1.0)
+            } else {
+                0.0
+            }
+        };
+        Self::new(axis(self.x), axis(self.y))
+    }
+}
+
+pub trait AnalogInputEventReciever {
+    fn update_analog(&mut self, analog_event: &AnalogInputEvent);
+}
+
+/// Largest [`StickSettings::deadzone`], leaving some travel to move with
+pub const MAX_DEADZONE: f32 = 0.95;
+pub const MIN_RESPONSE_EXPONENT: f32 = 0.1;
+pub const MAX_RESPONSE_EXPONENT: f32 = 10.0;
+
+/// How raw stick positions become movement
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct StickSettings {
+    /// Deflections below this are ignored, the rest is rescaled to [0, 1]
+    pub deadzone: f32,
+    /// Deflection is raised to this power, above 1 gives finer control near the center
+    pub response_exponent: f32,
+}
+
+impl Default for StickSettings {
+    fn default() -> Self {
+        Self {
+            deadzone: 0.15,
+            response_exponent: 2.0,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl StickSettings {
+    /// Out of range values are clamped, see [`StickSettings::clamped`]
+    #[wasm_bindgen(constructor)]
+    pub fn new(deadzone: f32, response_exponent: f32) -> Self {
+        Self {
+            deadzone,
+            response_exponent,
+        }
+        .clamped()
+    }
+}
+
+impl StickSettings {
+    /// The deadzone within [0, [`MAX_DEADZONE`]] and the exponent within
+    /// [[`MIN_RESPONSE_EXPONENT`], [`MAX_RESPONSE_EXPONENT`]], NaN gives no
+    /// deadzone and a linear response
+    pub fn clamped(&self) -> Self {
+        let deadzone = if self.deadzone.is_nan() {
+            0.0
+        } else {
+            self.deadzone.clamp(0.0, MAX_DEADZONE)
+        };
+        let response_exponent = if self.response_exponent.is_nan() {
+            1.0
+        } else {
+            self.response_exponent
+                .clamp(MIN_RESPONSE_EXPONENT, MAX_RESPONSE_EXPONENT)
+        };
+        Self {
+            deadzone,
+            response_exponent,
+        }
+    }
+
+    /// Apply the radial deadzone and the response curve to a raw stick position
+    pub fn apply(&self, analog_event: &AnalogInputEvent) -> AnalogInputEvent {
+        let Self {
+            deadzone,
+            response_exponent,
+        } = self.clamped();
+        let analog_event = analog_event.sanitized();
+        let raw = Vector2::new(analog_event.x, analog_event.y);
+        let deflection = raw.norm().min(1.0);
+        if deflection <= deadzone {
+            return AnalogInputEvent::new(0.0, 0.0);
+        }
+        let scaled = (deflection - deadzone) / (1.0 - deadzone);
+        let stick = raw.normalize() * scaled.powf(response_exponent);
+        AnalogInputEvent::new(stick.x, stick.y)
+    }
+}
@@ -0,0 +1,65 @@
+//! Best runs, each with the settings it was played with so that runs helped
+//! by the [`Accessibility`] options can be told apart
+
+use serde::{Deserialize, Serialize};
+
+use crate::{accessibility::Accessibility, difficulty::DifficultyPreset};
+
+/// Entries kept, the lowest scores are dropped beyond that
+pub const LEADERBOARD_SIZE: usize = 10;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub name: String,
+    pub score: u32,
+    pub ticks: u32,
+    pub seed: u64,
+    pub players: u8,
+    pub difficulty: DifficultyPreset,
+    pub adaptive: bool,
+    #[serde(default)]
+    pub accessibility: Accessibility,
+}
+
+impl LeaderboardEntry {
+    /// Whether the run was made easier than the preset: assist mode, or a
+    /// hitbox smaller than the usual one
+    pub fn assisted(&self) -> bool {
+        self.accessibility.assist || self.accessibility.clamped_hitbox_scale() < 1.0
+    }
+}
+
+/// Best score first
+#[derive(Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Leaderboard {
+    entries: Vec<LeaderboardEntry>,
+}
+
+impl Leaderboard {
+    pub fn entries(&self) -> &[LeaderboardEntry] {
+        &self.entries
+    }
+
+    /// Add `entry` after the ones with the same score, returns its rank from
+    /// 0, or `None` if it didn't make it
+    pub fn insert(&mut self, entry: LeaderboardEntry) -> Option<usize> {
+        let rank = self
+            .entries
+            .partition_point(|other| other.score >= entry.score);
+        if rank >= LEADERBOARD_SIZE {
+            return None;
+        }
+        self.entries.insert(rank, entry);
+        self.entries.truncate(LEADERBOARD_SIZE);
+        Some(rank)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|err| format!("invalid leaderboard: {err}"))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+}
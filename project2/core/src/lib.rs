@@ -1,94 +1,274 @@
+pub mod accessibility;
+pub mod achievement;
 pub mod animation;
+pub mod assets;
 pub mod attribute;
+pub mod audio;
+pub mod background;
+pub mod checksum;
+pub mod clock;
+pub mod difficulty;
+pub mod director;
 pub mod entity;
+pub mod event;
+pub mod headless;
 pub mod input;
+pub mod leaderboard;
+pub mod log;
+pub mod netcode;
+pub mod particle;
+pub mod popup;
 pub mod render;
+pub mod replay;
+pub mod rewind;
+pub mod scoring;
+pub mod snapshot;
+pub mod stats;
 
+use accessibility::Accessibility;
+use achievement::{AchievementStore, Achievements};
+use audio::{AudioCue, AudioDirector};
+use clock::{Clock, BOSS_SLOW_MOTION_TICKS, MS_PER_TICK};
+use difficulty::{Difficulty, DifficultyPreset};
+use director::Director;
 use entity::{
-    bullet::{self, Bullet},
+    bullet::Bullet,
     collisioned,
-    enemy::{Enemy, Enemy1, Enemy2, Enemy3},
+    enemy::{Enemy, Enemy3, EnemyKind},
     hero::Hero,
-    CollisionBox, EntityState, MotionState,
+    within, EntityState,
 };
-use input::{UserInputEvent, UserInputEventReciever};
+use event::GameEvent;
+use input::{
+    Action, ActionEvent, ActionEventReciever, AnalogInputEvent, AnalogInputEventReciever, InputMap,
+    PointerEvent, PointerEventReciever, StickSettings, UserInput, UserInputEvent,
+};
+use leaderboard::{Leaderboard, LeaderboardEntry};
+use log::{LogCategory, LogLevel};
 use nalgebra::Vector2;
+use particle::{EmitterKind, ParticleSystem};
+use popup::ScorePopups;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg32;
 use render::Primitive;
+use replay::{RecordedInput, Replay, ReplayInput};
+use rewind::{History, RewindSettings};
+use scoring::{kill_points, Scoring};
+use serde::{Deserialize, Serialize};
+use snapshot::{Snapshot, SNAPSHOT_VERSION};
+use stats::RunStats;
 
-use instant::Instant;
 use std::rc::Rc;
-use web_sys::console;
-// use std::time::Instant;
 
-use wasm_bindgen::{convert::IntoWasmAbi, prelude::*};
+use wasm_bindgen::prelude::*;
 
 use crate::render::Render;
 
 // settings states and game
 
 #[wasm_bindgen]
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub struct GameSettings {
     pub width: u16,
     pub height: u16,
+    /// Seed of the game's RNG, a random one is picked by the constructor
+    pub seed: u64,
+    /// Local players, each with their own hero and input map
+    pub players: u8,
+    /// Lives of each hero
+    pub lives: u8,
+    /// How the enemies get harder over the run, see [`Difficulty::at`]
+    #[serde(default)]
+    pub difficulty: DifficultyPreset,
+    /// Let the [`Director`] nudge the difficulty to the player's performance
+    #[serde(default)]
+    pub adaptive: bool,
+    #[serde(default)]
+    pub accessibility: Accessibility,
 }
 
 #[wasm_bindgen]
 impl GameSettings {
     #[wasm_bindgen(constructor)]
     pub fn new(width: u16, height: u16) -> Self {
-        Self { width, height }
+        Self {
+            width,
+            height,
+            seed: rand::random(),
+            players: 1,
+            lives: 1,
+            difficulty: DifficultyPreset::Normal,
+            adaptive: false,
+            accessibility: Accessibility::default(),
+        }
     }
 }
 
 #[wasm_bindgen]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct GameStates {
+    /// Total score of all the players
     score: u32,
-    hero: Hero,
-    hero_bullets: Vec<Box<Bullet>>,
-    enemies: Vec<Box<Enemy>>,
-    enemy_bullets: Vec<Box<Bullet>>,
+    /// One per player, indexed by player
+    heroes: Vec<Hero>,
+    hero_bullets: Vec<Bullet>,
+    enemies: Vec<Enemy>,
+    enemy_bullets: Vec<Bullet>,
     spawn_cooldown: u32,
+    rng: Pcg32,
+    stats: RunStats,
+    /// Current allowed enemy count, see [`GameEvent::WaveStarted`]
+    wave: u32,
+    #[serde(default)]
+    scoring: Scoring,
+    /// Only used with [`GameSettings::adaptive`]
+    #[serde(default)]
+    director: Director,
+    /// Events emitted by the last tick
+    #[serde(skip)]
+    events: Vec<GameEvent>,
 }
 
 const MAX_ENEMY_SPAWN_PER_TICK: u32 = 3;
-const SPAWN_COOLDOWN: u32 = 10;
+/// How close an enemy bullet must get to a hero to count as a near miss
+const NEAR_MISS_MARGIN: f32 = 16.0;
 
 impl GameStates {
-    pub fn new() -> Self {
+    pub fn new(settings: &GameSettings) -> Self {
+        let players = settings.players.max(1);
         Self {
             score: 0,
-            hero: Hero::new(0.0, 0.0),
+            // spread along the bottom, a single hero starts in the corner
+            heroes: (0..players)
+                .map(|player| {
+                    let x = settings.width as f32 * player as f32 / players as f32;
+                    let lives = (settings.lives.max(1))
+                        .saturating_add(settings.accessibility.extra_lives());
+                    Hero::new(player, x, 0.0, lives)
+                        .with_hitbox_scale(settings.accessibility.clamped_hitbox_scale())
+                })
+                .collect(),
             hero_bullets: vec![],
             enemies: vec![],
             enemy_bullets: vec![],
             spawn_cooldown: 0,
+            rng: Pcg32::seed_from_u64(settings.seed),
+            stats: RunStats::default(),
+            wave: 0,
+            scoring: Scoring::default(),
+            director: Director::default(),
+            events: vec![],
         }
     }
 
-    pub fn update(&mut self, user_input_event: &UserInputEvent) {
-        self.hero.update(user_input_event);
+    /// Whether a super big cup is on screen and not dying
+    pub fn boss_alive(&self) -> bool {
+        self.enemies.iter().any(|enemy| {
+            matches!(
+                enemy,
+                Enemy::SuperBigCup(enemy)
+                    if matches!(enemy.state, EntityState::Normal | EntityState::HittedAnimating)
+            )
+        })
+    }
+
+    /// Stable hash of the state, see [`checksum::hash_states`]
+    pub fn checksum(&self) -> u64 {
+        checksum::hash_states(self)
+    }
+
+    /// Whether every hero is out of lives
+    pub fn all_heroes_out(&self) -> bool {
+        self.heroes.iter().all(Hero::out)
+    }
+
+    /// One per player, indexed by player
+    pub fn heroes(&self) -> &[Hero] {
+        &self.heroes
+    }
+
+    pub fn update(&mut self, player: u8, action_event: &ActionEvent) {
+        game_log!(
+            Trace,
+            Input,
+            "player {player}: {:?} {}",
+            action_event.action,
+            if action_event.pressed { "down" } else { "up" }
+        );
+        if let Some(hero) = self.heroes.get_mut(player as usize) {
+            hero.update(action_event);
+        }
+    }
+
+    pub fn update_analog(&mut self, player: u8, analog_event: &AnalogInputEvent) {
+        game_log!(
+            Trace,
+            Input,
+            "player {player}: stick at ({}, {})",
+            analog_event.x,
+            analog_event.y
+        );
+        if let Some(hero) = self.heroes.get_mut(player as usize) {
+            hero.update_analog(analog_event);
+        }
+    }
+
+    pub fn update_pointer(&mut self, player: u8, pointer_event: &PointerEvent) {
+        game_log!(
+            Trace,
+            Input,
+            "player {player}: pointer {:?} at ({}, {})",
+            pointer_event.phase,
+            pointer_event.x,
+            pointer_event.y
+        );
+        if let Some(hero) = self.heroes.get_mut(player as usize) {
+            hero.update_pointer(pointer_event);
+        }
+    }
+
+    /// The difficulty of the game so far, adjusted by the director if enabled
+    pub fn difficulty(&self, settings: &GameSettings) -> Difficulty {
+        let difficulty = Difficulty::at(settings.difficulty, self.score, self.stats.ticks);
+        if settings.adaptive {
+            self.director.adjust(difficulty)
+        } else {
+            difficulty
+        }
     }
 
     pub fn tick(&mut self, settings: &GameSettings) {
-        // internal state of hero
-        self.hero.tick(settings);
-
-        if self.hero.shooting {
-            if self.hero.shooting_cooldown <= 0 {
-                self.hero_bullets.push(Box::new(Bullet {
-                    motion_state: MotionState {
-                        pos: self.hero.motion_state.pos,
-                        speed: Vector2::y() * 8.0,
-                        ..Default::default()
-                    },
-                }));
-                self.hero.shooting_cooldown = 10;
-            } else {
-                self.hero.shooting_cooldown -= 1;
+        self.stats.ticks += 1;
+        self.events.clear();
+
+        for hero in self.heroes.iter_mut() {
+            // internal state of hero
+            let hero_state = hero.state;
+            let animation_events = hero.tick(settings);
+            let pos = hero.motion_state.pos;
+            push_animation_events(&mut self.events, animation_events, (pos.x, pos.y));
+            if hero_state != EntityState::Died && hero.state == EntityState::Died {
+                self.events.push(GameEvent::HeroDied {
+                    player: hero.player,
+                    pos: (pos.x, pos.y),
+                });
             }
-        } else {
-            if self.hero.shooting_cooldown > 0 {
-                self.hero.shooting_cooldown -= 1;
+
+            if hero.firing() && hero.state == EntityState::Normal {
+                if hero.shooting_cooldown == 0 {
+                    let fired = self.hero_bullets.len();
+                    self.hero_bullets.extend(hero.shoot());
+                    self.stats.bullets_fired += (self.hero_bullets.len() - fired) as u32;
+                    self.events.push(GameEvent::BulletFired {
+                        player: hero.player,
+                        pos: (pos.x, pos.y),
+                    });
+                    hero.shooting_cooldown = hero.weapon.cooldown;
+                } else {
+                    hero.shooting_cooldown -= 1;
+                }
+            } else if hero.shooting_cooldown > 0 {
+                hero.shooting_cooldown -= 1;
             }
         }
 
@@ -98,30 +278,28 @@ impl GameStates {
         }
 
         // spawn enemies
+        let difficulty = self.difficulty(settings);
         self.spawn_cooldown = self.spawn_cooldown.saturating_sub(1);
         if self.spawn_cooldown == 0 {
-            self.spawn_cooldown = SPAWN_COOLDOWN;
-            let max_enemy_cnt = get_total_cnt_by_score(self.score);
-            let gen_frac = get_gen_frac_by_score(self.score);
-            let enemy_type = rand::random::<f32>();
-            let gen_enemy = || {
-                let x = rand::random::<f32>() * settings.width as f32;
-                if enemy_type < gen_frac.0 {
-                    Enemy::MiddleCup(Enemy1::new(x, settings.height as f32))
-                } else if enemy_type < gen_frac.1 {
-                    Enemy::BigCup(Enemy2::new(x, settings.height as f32))
-                } else if enemy_type < gen_frac.2 {
-                    Enemy::SuperBigCup(Enemy3::new(x, settings.height as f32))
-                } else {
-                    Enemy::MiddleCup(Enemy1::new(x, settings.height as f32))
-                    // panic!("Never reach");
-                }
+            self.spawn_cooldown = difficulty.spawn_interval;
+            if difficulty.max_enemies > self.wave {
+                self.wave = difficulty.max_enemies;
+                self.events.push(GameEvent::WaveStarted { wave: self.wave });
+            }
+            let kind = difficulty.pick(self.rng.gen::<f32>());
+            let mut gen_enemy = || {
+                let x = self.rng.gen::<f32>() * settings.width as f32;
+                game_log!(Debug, Spawn, "{kind:?} spawned at x = {x}");
+                let mut enemy = Enemy::new(kind, x, settings.height as f32);
+                enemy.motion_state_mut().speed *= difficulty.speed_scale;
+                enemy.set_shooting_cooldown(difficulty.fire_interval);
+                enemy
             };
             let mut spawn_cnt = 0;
-            while self.enemies.len() < max_enemy_cnt as usize {
+            while self.enemies.len() < difficulty.max_enemies as usize {
                 spawn_cnt += 1;
                 let enemy = gen_enemy();
-                self.enemies.push(Box::new(enemy));
+                self.enemies.push(enemy);
                 if spawn_cnt >= MAX_ENEMY_SPAWN_PER_TICK {
                     break;
                 }
@@ -130,79 +308,208 @@ impl GameStates {
 
         // tick enemies
         for enemy in &mut self.enemies {
-            match enemy.as_mut() {
+            match enemy {
                 Enemy::MiddleCup(enemy) => {
+                    let pos = (enemy.motion_state.pos.x, enemy.motion_state.pos.y);
                     if enemy.health == 0 && enemy.state != EntityState::DieAnimating {
                         enemy.state = EntityState::DieAnimating;
+                        self.events.push(GameEvent::EnemyKilled {
+                            kind: EnemyKind::MiddleCup,
+                            pos,
+                            score: kill_points(EnemyKind::MiddleCup),
+                            player: enemy.last_hit_by,
+                        });
                     }
-                    if collisioned(enemy, &self.hero) && self.hero.state == EntityState::Normal {
-                        self.hero.state = EntityState::DieAnimating
+                    for hero in self.heroes.iter_mut() {
+                        if collisioned(enemy, hero) && hero.hit() {
+                            self.stats.hits_taken += 1;
+                            game_log!(Info, Collision, "hero {} hit", hero.player);
+                            let pos = hero.motion_state.pos;
+                            self.events.push(GameEvent::HeroHit {
+                                player: hero.player,
+                                pos: (pos.x, pos.y),
+                            });
+                        }
                     }
-                    enemy.tick(settings)
+                    let animation_events = enemy.tick(settings);
+                    push_animation_events(&mut self.events, animation_events, pos);
                 }
                 Enemy::BigCup(enemy) => {
+                    let pos = (enemy.motion_state.pos.x, enemy.motion_state.pos.y);
                     if enemy.health == 0 && enemy.state != EntityState::DieAnimating {
                         enemy.state = EntityState::DieAnimating;
+                        self.events.push(GameEvent::EnemyKilled {
+                            kind: EnemyKind::BigCup,
+                            pos,
+                            score: kill_points(EnemyKind::BigCup),
+                            player: enemy.last_hit_by,
+                        });
                     }
-                    if collisioned(enemy, &self.hero) && self.hero.state == EntityState::Normal {
-                        self.hero.state = EntityState::DieAnimating
+                    for hero in self.heroes.iter_mut() {
+                        if collisioned(enemy, hero) && hero.hit() {
+                            self.stats.hits_taken += 1;
+                            game_log!(Info, Collision, "hero {} hit", hero.player);
+                            let pos = hero.motion_state.pos;
+                            self.events.push(GameEvent::HeroHit {
+                                player: hero.player,
+                                pos: (pos.x, pos.y),
+                            });
+                        }
                     }
-                    enemy.tick(settings)
+                    let animation_events = enemy.tick(settings);
+                    push_animation_events(&mut self.events, animation_events, pos);
                 }
                 Enemy::SuperBigCup(enemy) => {
+                    let pos = (enemy.motion_state.pos.x, enemy.motion_state.pos.y);
                     if enemy.health == 0 && enemy.state != EntityState::DieAnimating {
                         enemy.state = EntityState::DieAnimating;
+                        self.events.push(GameEvent::EnemyKilled {
+                            kind: EnemyKind::SuperBigCup,
+                            pos,
+                            score: kill_points(EnemyKind::SuperBigCup),
+                            player: enemy.last_hit_by,
+                        });
                     }
-                    if collisioned(enemy, &self.hero) && self.hero.state == EntityState::Normal {
-                        self.hero.state = EntityState::DieAnimating
+                    for hero in self.heroes.iter_mut() {
+                        if collisioned(enemy, hero) && hero.hit() {
+                            self.stats.hits_taken += 1;
+                            game_log!(Info, Collision, "hero {} hit", hero.player);
+                            let pos = hero.motion_state.pos;
+                            self.events.push(GameEvent::HeroHit {
+                                player: hero.player,
+                                pos: (pos.x, pos.y),
+                            });
+                        }
                     }
-                    enemy.tick(settings)
+                    let animation_events = enemy.tick(settings);
+                    push_animation_events(&mut self.events, animation_events, pos);
                 }
             }
         }
 
+        // enemies fire at the closest hero still in the game
+        let bullet_speed = settings.accessibility.enemy_bullet_speed();
+        for enemy in &mut self.enemies {
+            let pos = enemy.motion_state().pos;
+            let target = self
+                .heroes
+                .iter()
+                .filter(|hero| !hero.out())
+                .map(|hero| hero.motion_state.pos)
+                .min_by(|a, b| (a - pos).norm().total_cmp(&(b - pos).norm()));
+            self.enemy_bullets
+                .extend(enemy.fire(target, difficulty.fire_interval, bullet_speed));
+        }
+
+        // Retain enemy_bullets:
+        // - collisioned with a vulnerable hero: the hero is hit, remove bullet
+        // - close to a vulnerable hero: near miss, once per bullet
+        // - out of screen: simply remove
+        for bullet in self.enemy_bullets.iter_mut() {
+            bullet.motion_state.tick(settings);
+        }
+        self.enemy_bullets.retain_mut(|bullet| {
+            for hero in self.heroes.iter_mut() {
+                if !bullet.grazed
+                    && hero.vulnerable()
+                    && within(bullet, hero, NEAR_MISS_MARGIN)
+                    && !collisioned(bullet, hero)
+                {
+                    bullet.grazed = true;
+                    self.stats.grazes += 1;
+                    let pos = bullet.motion_state.pos;
+                    self.events.push(GameEvent::NearMiss {
+                        player: hero.player,
+                        pos: (pos.x, pos.y),
+                    });
+                }
+                if collisioned(bullet, hero) && hero.hit() {
+                    self.stats.hits_taken += 1;
+                    game_log!(Info, Collision, "hero {} shot", hero.player);
+                    let pos = hero.motion_state.pos;
+                    self.events.push(GameEvent::HeroHit {
+                        player: hero.player,
+                        pos: (pos.x, pos.y),
+                    });
+                    return false;
+                }
+            }
+            let pos = bullet.motion_state.pos;
+            pos.x > 0.0
+                && pos.x < settings.width as f32
+                && pos.y > 0.0
+                && pos.y < settings.height as f32
+        });
+
         // Retain hero_bullets:
         // - collisioned: update enemy health, and remove bullet
         // - out of screen: simply remove
         self.hero_bullets.retain(|bullet| {
-            let bullet = bullet.as_ref();
             for enemy in &mut self.enemies {
-                match enemy.as_mut() {
+                match enemy {
                     Enemy::MiddleCup(enemy) => {
-                        if enemy.state == EntityState::DieAnimating
+                        // may already be down to 0 from another bullet of this tick
+                        if enemy.health == 0
+                            || enemy.state == EntityState::DieAnimating
                             || enemy.state == EntityState::Died
                         {
                             continue;
                         }
                         if collisioned(bullet, enemy) {
-                            console::log(&JsValue::from_str("collision 1").into());
+                            game_log!(Debug, Collision, "bullet hit enemy 1");
                             enemy.health -= 1;
+                            enemy.last_hit_by = bullet.owner;
+                            self.stats.bullets_hit += 1;
+                            let pos = enemy.motion_state.pos;
+                            self.events.push(GameEvent::EnemyHit {
+                                kind: EnemyKind::MiddleCup,
+                                pos: (pos.x, pos.y),
+                            });
                             return false;
                         }
                     }
                     Enemy::BigCup(enemy) => {
-                        if enemy.state == EntityState::DieAnimating
+                        if enemy.health == 0
+                            || enemy.state == EntityState::DieAnimating
                             || enemy.state == EntityState::Died
                         {
                             continue;
                         }
                         if collisioned(bullet, enemy) {
                             enemy.state = EntityState::HittedAnimating;
-                            console::log(&JsValue::from_str("collision 2").into());
+                            game_log!(Debug, Collision, "bullet hit enemy 2");
                             enemy.health -= 1;
+                            enemy.last_hit_by = bullet.owner;
+                            self.stats.bullets_hit += 1;
+                            let pos = enemy.motion_state.pos;
+                            self.events.push(GameEvent::EnemyHit {
+                                kind: EnemyKind::BigCup,
+                                pos: (pos.x, pos.y),
+                            });
                             return false;
                         }
                     }
                     Enemy::SuperBigCup(enemy) => {
-                        if enemy.state == EntityState::DieAnimating
+                        if enemy.health == 0
+                            || enemy.state == EntityState::DieAnimating
                             || enemy.state == EntityState::Died
                         {
                             continue;
                         }
                         if collisioned(bullet, enemy) {
                             enemy.state = EntityState::HittedAnimating;
-                            console::log(&JsValue::from_str("collision 3").into());
+                            game_log!(Debug, Collision, "bullet hit enemy 3");
                             enemy.health -= 1;
+                            enemy.last_hit_by = bullet.owner;
+                            self.stats.bullets_hit += 1;
+                            let pos = enemy.motion_state.pos;
+                            self.events.push(GameEvent::EnemyHit {
+                                kind: EnemyKind::SuperBigCup,
+                                pos: (pos.x, pos.y),
+                            });
+                            if enemy.health == Enemy3::MAX_HEALTH / 2 {
+                                self.events.push(GameEvent::BossPhaseChanged { phase: 1 });
+                            }
                             return false;
                         }
                     }
@@ -212,101 +519,241 @@ impl GameStates {
         });
 
         // Remove enemy with emtpy health and count score
-        self.enemies.retain(|enemy| match enemy.as_ref() {
+        self.enemies.retain(|enemy| match enemy {
             Enemy::MiddleCup(enemy) => {
-                if enemy.health <= 0 && enemy.state == EntityState::Died {
-                    self.score += 1;
+                if enemy.health == 0 && enemy.state == EntityState::Died {
+                    self.stats.kills.middle_cup += 1;
+                    game_log!(Debug, Scoring, "middle cup destroyed, score {}", self.score);
                     return false;
                 };
                 enemy.motion_state.pos.y > 0.0
             }
             Enemy::BigCup(enemy) => {
-                if enemy.health <= 0 && enemy.state == EntityState::Died {
-                    self.score += 5;
+                if enemy.health == 0 && enemy.state == EntityState::Died {
+                    self.stats.kills.big_cup += 1;
+                    game_log!(Debug, Scoring, "big cup destroyed, score {}", self.score);
                     return false;
                 };
                 enemy.motion_state.pos.y > 0.0
             }
             Enemy::SuperBigCup(enemy) => {
-                if enemy.health <= 0 && enemy.state == EntityState::Died {
-                    self.score += 10;
+                if enemy.health == 0 && enemy.state == EntityState::Died {
+                    self.stats.kills.super_big_cup += 1;
+                    game_log!(
+                        Debug,
+                        Scoring,
+                        "super big cup destroyed, score {}",
+                        self.score
+                    );
                     return false;
                 };
                 enemy.motion_state.pos.y > 0.0
             }
         });
-    }
-}
-
-const MAX_SMALL_ENEMY: u32 = 5;
-const MAX_MIDDLE_ENEMY: u32 = 3;
-const MAX_BIG_ENEMY: u32 = 2;
-const MAX_SPEED_FRAC: f32 = 2.0;
-
-/// Difficulty cauculation
-fn get_gen_frac_by_score(score: u32) -> (f32, f32, f32) {
-    let small = score / 3;
 
-    let middle = small / (MAX_SMALL_ENEMY + 1);
-    let small = small % (MAX_SMALL_ENEMY + 1);
-
-    let big = middle / (MAX_MIDDLE_ENEMY + 1);
-    let middle = middle % (MAX_MIDDLE_ENEMY + 1);
-
-    let big = big.min(MAX_BIG_ENEMY);
-
-    // let big = score / 1000 % 10;
-    // let middle = score / 100 % 10;
-    // let small = (score / 10 % 10).max(1);
-    let total = (small + middle + big).max(1);
-
-    (
-        (small as f32 / total as f32),
-        (middle as f32 / total as f32),
-        (big as f32 / total as f32),
-    )
+        self.score += self.scoring.award(&mut self.events, &mut self.heroes);
+        for hero in &self.heroes {
+            let combo = self.scoring.chain(hero.player).kills;
+            self.stats.max_combo = self.stats.max_combo.max(combo);
+        }
 
-    // (
-    //     (small as f32 / total as f32).min(MAX_SMALL_ENEMY as f32 / 15.0),
-    //     (middle as f32 / total as f32).min(MAX_MIDDLE_ENEMY as f32 / 15.0),
-    //     (big as f32 / total as f32).min(MAX_BIG_ENEMY as f32 / 15.0),
-    // )
+        if settings.adaptive {
+            if let Some(decision) = self.director.observe(&self.events) {
+                self.events.push(decision);
+            }
+        }
+    }
 }
 
-fn get_total_cnt_by_score(score: u32) -> u32 {
-    (score / 3).max(1).min(10)
+fn push_animation_events(events: &mut Vec<GameEvent>, names: Vec<&'static str>, pos: (f32, f32)) {
+    events.extend(
+        names
+            .into_iter()
+            .map(|name| GameEvent::AnimationEvent { name, pos }),
+    );
 }
 
 #[wasm_bindgen]
 pub struct Game {
     settings: Rc<GameSettings>,
-    last_tick_time: Instant,
+    clock: Clock,
     states: GameStates,
     render_primitives: Vec<Primitive>,
+    recorded_inputs: Vec<ReplayInput>,
+    /// Hash of the state after every tick, see [`GameStates::checksum`]
+    checksums: Vec<u64>,
+    /// One per player, indexed by player
+    input_maps: Vec<InputMap>,
+    stick_settings: StickSettings,
+    pending_events: Vec<GameEvent>,
+    audio: AudioDirector,
+    particles: ParticleSystem,
+    popups: ScorePopups,
+    achievements: Achievements,
+    history: History,
 }
 
+/// Undrained events beyond this are dropped, oldest first
+const MAX_PENDING_EVENTS: usize = 1024;
+/// Keeps the cosmetic RNG apart from the simulation one
+const PARTICLE_SEED_SALT: u64 = 0x9e37_79b9_7f4a_7c15;
+/// Distance from the hero's center to its engine
+const HERO_ENGINE_OFFSET: f32 = 60.0;
+
 #[wasm_bindgen]
 impl Game {
     #[wasm_bindgen(constructor)]
     pub fn new(setting: GameSettings) -> Self {
+        let states = GameStates::new(&setting);
+        let mut history = History::new(RewindSettings::default());
+        history.record(&states);
+        let mut particles = ParticleSystem::new(setting.seed ^ PARTICLE_SEED_SALT);
+        particles.set_flashes(setting.accessibility.screen_effects);
         Self {
+            states,
             settings: Rc::new(setting),
-            last_tick_time: Instant::now(),
-            states: GameStates::new(),
+            clock: Clock::default(),
             render_primitives: Vec::new(),
+            recorded_inputs: Vec::new(),
+            checksums: Vec::new(),
+            input_maps: (0..setting.players.max(1))
+                .map(|player| InputMap::for_player(player, setting.players))
+                .collect(),
+            stick_settings: StickSettings::default(),
+            pending_events: Vec::new(),
+            audio: AudioDirector::new(),
+            particles,
+            popups: ScorePopups::default(),
+            achievements: Achievements::default(),
+            history,
         }
     }
 
+    /// Feed a raw key event, translated to actions by the input map of every player
     pub fn update(&mut self, user_input_event: &UserInputEvent) {
-        self.states.update(user_input_event);
+        let key = user_input_event.key();
+        let actions: Vec<(u8, Action)> = (0..self.input_maps.len() as u8)
+            .flat_map(|player| {
+                self.input_maps[player as usize]
+                    .actions(&key)
+                    .map(move |action| (player, action))
+            })
+            .collect();
+        for (player, action) in actions {
+            self.action(player, action, user_input_event.pressed);
+        }
+    }
+
+    /// Feed an action of `player` directly, for inputs that are not keys
+    pub fn action(&mut self, player: u8, action: Action, pressed: bool) {
+        self.recorded_inputs.push(ReplayInput {
+            tick: self.states.stats.ticks,
+            input: RecordedInput::Action {
+                player,
+                action,
+                pressed,
+            },
+        });
+        self.states.update(player, &ActionEvent { action, pressed });
+    }
+
+    /// Whether `key` is bound to `action` for any player, lets the host react
+    /// to keys like pause
+    pub fn key_triggers(&self, key: &str, action: Action) -> bool {
+        self.input_maps
+            .iter()
+            .any(|map| map.actions(key).any(|bound| bound == action))
+    }
+
+    /// The input map of `player`, as JSON: an object of action to the list of its keys
+    pub fn input_map(&self, player: u8) -> Result<String, String> {
+        self.input_maps
+            .get(player as usize)
+            .map(InputMap::to_json)
+            .ok_or_else(|| format!("no player {player}"))
+    }
+
+    pub fn set_input_map(&mut self, player: u8, json: &str) -> Result<(), String> {
+        *self.player_input_map(player)? = InputMap::from_json(json)?;
+        Ok(())
+    }
+
+    pub fn bind_key(&mut self, player: u8, action: Action, key: &str) -> Result<(), String> {
+        self.player_input_map(player)?.bind(action, key);
+        Ok(())
+    }
+
+    pub fn unbind_key(&mut self, player: u8, action: Action, key: &str) -> Result<(), String> {
+        self.player_input_map(player)?.unbind(action, key);
+        Ok(())
+    }
+
+    /// Feed a button of the gamepad of `player`, bound in their input map as
+    /// `Gamepad<index>`
+    pub fn update_button(&mut self, player: u8, index: u32, pressed: bool) {
+        let Some(map) = self.input_maps.get(player as usize) else {
+            return;
+        };
+        let actions: Vec<Action> = map.actions(&format!("Gamepad{index}")).collect();
+        for action in actions {
+            self.action(player, action, pressed);
+        }
+    }
+
+    /// Feed a raw stick position of `player`, through the stick settings
+    pub fn update_analog(&mut self, player: u8, analog_event: &AnalogInputEvent) {
+        self.analog(player, &self.stick_settings.apply(analog_event));
+    }
+
+    /// Feed a stick position that already went through the stick settings,
+    /// see [`AnalogInputEvent::sanitized`]
+    pub fn analog(&mut self, player: u8, analog_event: &AnalogInputEvent) {
+        let analog_event = &analog_event.sanitized();
+        self.recorded_inputs.push(ReplayInput {
+            tick: self.states.stats.ticks,
+            input: RecordedInput::Analog {
+                player,
+                x: analog_event.x,
+                y: analog_event.y,
+            },
+        });
+        self.states.update_analog(player, analog_event);
+    }
+
+    pub fn set_stick_settings(&mut self, stick_settings: StickSettings) {
+        self.stick_settings = stick_settings;
+    }
+
+    /// Feed a mouse or touch event dragging the hero of `player`
+    pub fn update_pointer(&mut self, player: u8, pointer_event: &PointerEvent) {
+        self.recorded_inputs.push(ReplayInput {
+            tick: self.states.stats.ticks,
+            input: RecordedInput::Pointer {
+                player,
+                phase: pointer_event.phase,
+                x: pointer_event.x,
+                y: pointer_event.y,
+            },
+        });
+        self.states.update_pointer(player, pointer_event);
     }
 
     pub fn prepare_primitives(&mut self) {
-        let ms_delta = self.last_tick_time.elapsed().as_millis();
+        let ms_delta = self.clock.ms_delta();
 
         self.render_primitives.clear();
+        let time = self.states.stats.ticks as f64 + ms_delta as f64 / MS_PER_TICK;
         self.render_primitives
-            .push(self.states.hero.render(ms_delta));
+            .extend(background::render(&self.settings, self.states.wave, time));
+        let accessibility = self.settings.accessibility;
+        self.render_primitives
+            .extend(self.states.heroes.iter().map(|hero| {
+                if accessibility.screen_effects {
+                    hero.render(ms_delta)
+                } else {
+                    hero.render_steady(ms_delta)
+                }
+            }));
         self.render_primitives.extend(
             self.states
                 .hero_bullets
@@ -319,17 +766,119 @@ impl Game {
                 .iter()
                 .map(|enemy| enemy.render(ms_delta)),
         );
+        self.render_primitives
+            .extend(self.particles.render(ms_delta));
+        self.render_primitives
+            .extend(self.states.enemy_bullets.iter().map(|bullet| {
+                let primitive = bullet.render(ms_delta);
+                if accessibility.high_contrast {
+                    primitive.with_tint(accessibility.enemy_bullet_tint())
+                } else {
+                    primitive
+                }
+            }));
         self.render_primitives.extend(
             self.states
-                .enemy_bullets
+                .heroes
                 .iter()
-                .map(|bullet| bullet.render(ms_delta)),
+                .filter_map(|hero| hero.hitbox_indicator(ms_delta)),
         );
+        self.render_primitives.extend(self.popups.render(ms_delta));
     }
 
+    /// Run one tick right away, the interpolation restarts from it
     pub fn tick(&mut self) {
-        self.states.tick(&self.settings);
-        self.last_tick_time = Instant::now();
+        self.run_tick();
+        self.clock.reset();
+    }
+
+    /// Let `elapsed_ms` of real time pass, running the ticks it is worth at
+    /// the current time scale, returns how many ran.
+    ///
+    /// Whatever is left short of a tick is what [`Game::prepare_primitives`]
+    /// interpolates with.
+    pub fn advance(&mut self, elapsed_ms: f64) -> u32 {
+        let due = self.clock.advance(elapsed_ms);
+        let mut ran = 0;
+        while ran < due && !self.end() {
+            self.run_tick();
+            ran += 1;
+        }
+        ran
+    }
+
+    /// How many ticks run per real second, relative to the normal pace, from
+    /// [`clock::MIN_TIME_SCALE`] to [`clock::MAX_TIME_SCALE`]
+    pub fn set_time_scale(&mut self, scale: f32) {
+        self.clock.set_scale(scale);
+    }
+
+    /// The time scale in use, slow motions included
+    pub fn time_scale(&self) -> f32 {
+        self.clock.effective_scale()
+    }
+
+    /// Bullet time for `ticks` ticks
+    pub fn slow_motion(&mut self, ticks: u32) {
+        self.clock.slow_motion(ticks);
+    }
+
+    fn run_tick(&mut self) {
+        self.step();
+        self.pending_events.extend_from_slice(&self.states.events);
+        for achievement in self
+            .achievements
+            .check(&self.states.stats, self.states.score)
+        {
+            game_log!(Info, Scoring, "achievement {} unlocked", achievement.id);
+            self.pending_events
+                .push(GameEvent::AchievementUnlocked { id: achievement.id });
+        }
+        if self.pending_events.len() > MAX_PENDING_EVENTS {
+            let overflow = self.pending_events.len() - MAX_PENDING_EVENTS;
+            self.pending_events.drain(..overflow);
+        }
+        self.audio.tick(
+            &self.states.events,
+            self.states.boss_alive(),
+            self.states.all_heroes_out(),
+        );
+
+        self.particles.tick();
+        self.particles.handle_events(&self.states.events);
+        self.popups.tick();
+        self.popups.handle_events(&self.states.events);
+        for hero in &self.states.heroes {
+            if hero.state == EntityState::Normal && hero.motion_state.speed != Vector2::zeros() {
+                let pos = hero.motion_state.pos;
+                self.particles.emit(
+                    EmitterKind::EngineTrail,
+                    (pos.x, pos.y - HERO_ENGINE_OFFSET),
+                );
+            }
+        }
+        self.clock.tick();
+        if self.states.events.iter().any(|event| {
+            matches!(
+                event,
+                GameEvent::EnemyKilled {
+                    kind: EnemyKind::SuperBigCup,
+                    ..
+                }
+            )
+        }) {
+            self.clock.slow_motion(BOSS_SLOW_MOTION_TICKS);
+        }
+    }
+
+    /// Events emitted since the last drain, as a JSON array of [`GameEvent`]
+    pub fn drain_events(&mut self) -> String {
+        serde_json::to_string(&self.take_events()).unwrap()
+    }
+
+    /// Sound and music cues queued since the last drain, as a JSON array of [`AudioCue`]
+    pub fn drain_audio_cues(&mut self) -> String {
+        serde_json::to_string(&self.take_audio_cues()).unwrap()
     }
 
     // pub fn hero(&self) -> Hero {
@@ -345,25 +894,284 @@ impl Game {
     pub fn primitives_len(&self) -> usize {
         self.render_primitives.len()
     }
+    /// Size of a [`Primitive`] in 4 bytes words
+    pub fn primitive_size(&self) -> usize {
+        std::mem::size_of::<Primitive>() / 4
+    }
 
     pub fn debug_info(&self) -> String {
         format!(
             "score: {},<br/>position: ({}, {}),<br/>speed: ({}, {})<br/>shooting: {}, {}",
             self.states.score,
-            self.states.hero.motion_state.pos.x,
-            self.states.hero.motion_state.pos.y,
-            self.states.hero.motion_state.speed.x,
-            self.states.hero.motion_state.speed.y,
-            self.states.hero.shooting,
-            self.states.hero.shooting_cooldown
+            self.states.heroes[0].motion_state.pos.x,
+            self.states.heroes[0].motion_state.pos.y,
+            self.states.heroes[0].motion_state.speed.x,
+            self.states.heroes[0].motion_state.speed.y,
+            self.states.heroes[0].firing(),
+            self.states.heroes[0].shooting_cooldown
         )
     }
 
+    /// Whether every player is out of lives
     pub fn end(&self) -> bool {
-        self.states.hero.state == EntityState::Died
+        self.states.all_heroes_out()
     }
 
+    /// Hash of the current state, see [`GameStates::checksum`]
+    pub fn checksum(&self) -> u64 {
+        self.states.checksum()
+    }
+
+    /// Total score of all the players
     pub fn score(&self) -> u32 {
         self.states.score
     }
+
+    pub fn players(&self) -> u8 {
+        self.states.heroes.len() as u8
+    }
+
+    /// Score of `player`, 0 for a player not in the game
+    pub fn player_score(&self, player: u8) -> u32 {
+        self.states
+            .heroes
+            .get(player as usize)
+            .map_or(0, |hero| hero.score)
+    }
+
+    /// Statistics of the run so far as JSON, see [`RunStats`], with the accuracy
+    pub fn run_stats(&self) -> String {
+        let mut stats = serde_json::to_value(self.states.stats).unwrap();
+        stats["accuracy"] = self.states.stats.accuracy().into();
+        stats.to_string()
+    }
+
+    /// Add this run under `name` to `leaderboard`, a JSON [`Leaderboard`],
+    /// returns the updated one, a new one if it is invalid
+    pub fn add_to_leaderboard(&self, leaderboard: &str, name: &str) -> String {
+        let mut leaderboard = Leaderboard::from_json(leaderboard).unwrap_or_default();
+        leaderboard.insert(self.leaderboard_entry(name));
+        leaderboard.to_json()
+    }
+
+    /// Every achievement with whether it is unlocked, as JSON
+    pub fn achievements(&self) -> String {
+        self.achievements.to_json()
+    }
+
+    /// Current kill chain multiplier of `player`, see [`scoring::Chain`]
+    pub fn chain_multiplier(&self, player: u8) -> u32 {
+        self.states.scoring.chain(player).multiplier()
+    }
+
+    /// Lives left of `player`, 0 for a player not in the game
+    pub fn player_lives(&self, player: u8) -> u8 {
+        self.states
+            .heroes
+            .get(player as usize)
+            .map_or(0, |hero| hero.lives)
+    }
+
+    /// Drop log messages less severe than `level`, for every game since the
+    /// logger is shared
+    pub fn set_log_level(level: LogLevel) {
+        log::set_level(level);
+    }
+
+    pub fn set_log_category(category: LogCategory, enabled: bool) {
+        log::set_category_enabled(category, enabled);
+    }
+
+    /// The whole state of the run, as a JSON [`Snapshot`]
+    pub fn save_state(&self) -> String {
+        Snapshot {
+            version: SNAPSHOT_VERSION,
+            settings: *self.settings,
+            states: self.states.clone(),
+            inputs: self.recorded_inputs.clone(),
+            checksums: self.checksums.clone(),
+        }
+        .to_json()
+    }
+
+    /// Continue from a state saved by [`Game::save_state`], by a game with as
+    /// many players, the game is left untouched if the snapshot is invalid
+    pub fn load_state(&mut self, json: &str) -> Result<(), String> {
+        let snapshot = Snapshot::from_json(json)?;
+        let players = snapshot.settings.players.max(1) as usize;
+        if snapshot.states.heroes.len() != players {
+            return Err(format!(
+                "invalid snapshot: {} heroes for {players} players",
+                snapshot.states.heroes.len()
+            ));
+        }
+        if players != self.input_maps.len() {
+            return Err(format!(
+                "snapshot of a {players} players game, this one has {}",
+                self.input_maps.len()
+            ));
+        }
+        self.settings = Rc::new(snapshot.settings);
+        self.particles
+            .set_flashes(self.settings.accessibility.screen_effects);
+        self.states = snapshot.states;
+        self.recorded_inputs = snapshot.inputs;
+        self.checksums = snapshot.checksums;
+        self.history.reset(self.history.settings(), &self.states);
+        self.resync_cosmetics();
+        Ok(())
+    }
+
+    /// Change how much history is kept for [`Game::rewind`], forgets the current one
+    pub fn set_rewind_settings(&mut self, rewind_settings: RewindSettings) {
+        self.history.reset(rewind_settings, &self.states);
+    }
+
+    /// How far back [`Game::rewind`] can go, in ticks
+    pub fn rewind_available(&self) -> u32 {
+        self.history
+            .oldest_tick()
+            .map_or(0, |oldest| self.states.stats.ticks - oldest)
+    }
+
+    /// Go back `ticks` ticks, or as far as the history allows, and returns how
+    /// far it went.
+    ///
+    /// The closest earlier snapshot is restored, then the recorded inputs are
+    /// replayed up to the target tick, so the result is exactly the state
+    /// the run was in. Inputs received after it are dropped, and what the
+    /// players hold right now is fed again as new inputs, since they are
+    /// still holding it.
+    pub fn rewind(&mut self, ticks: u32) -> u32 {
+        let now = self.states.stats.ticks;
+        let Some(oldest) = self.history.oldest_tick() else {
+            return 0;
+        };
+        let target = now.saturating_sub(ticks).max(oldest);
+        let Some(states) = self.history.restore(target) else {
+            return 0;
+        };
+        let held: Vec<UserInput> = self.states.heroes.iter().map(|hero| hero.input).collect();
+        self.states = states;
+        self.checksums.truncate(self.states.stats.ticks as usize);
+
+        let inputs = std::mem::take(&mut self.recorded_inputs);
+        let (kept, replayed): (Vec<_>, Vec<_>) = inputs
+            .into_iter()
+            .filter(|input| input.tick < target)
+            .partition(|input| input.tick < self.states.stats.ticks);
+        self.recorded_inputs = kept;
+        let mut replayed = replayed.into_iter().peekable();
+        while self.states.stats.ticks < target {
+            let tick = self.states.stats.ticks;
+            while let Some(input) = replayed.next_if(|input| input.tick <= tick) {
+                input.apply(self);
+            }
+            self.step();
+        }
+
+        for (player, held) in held.iter().enumerate() {
+            let Some(hero) = self.states.heroes.get(player) else {
+                continue;
+            };
+            let input = hero.input;
+            for event in input.changes_to(held) {
+                self.action(player as u8, event.action, event.pressed);
+            }
+            if input.stick != held.stick {
+                let stick = AnalogInputEvent::new(held.stick.x, held.stick.y);
+                self.analog(player as u8, &stick);
+            }
+        }
+
+        game_log!(Info, Input, "rewound from tick {now} to {target}");
+        self.resync_cosmetics();
+        now - target
+    }
+
+    /// The seed and every input received so far, as a JSON [`Replay`]
+    pub fn export_replay(&self) -> String {
+        Replay {
+            seed: self.settings.seed,
+            width: self.settings.width,
+            height: self.settings.height,
+            players: self.settings.players,
+            lives: self.settings.lives,
+            difficulty: self.settings.difficulty,
+            adaptive: self.settings.adaptive,
+            accessibility: self.settings.accessibility,
+            inputs: self.recorded_inputs.clone(),
+            checksums: self.checksums.clone(),
+        }
+        .to_json()
+    }
+}
+
+impl Game {
+    /// Advance the simulation only, everything cosmetic is left to [`Game::tick`]
+    fn step(&mut self) {
+        self.states.tick(&self.settings);
+        self.checksums.push(self.states.checksum());
+        self.history.record(&self.states);
+    }
+
+    /// Drop everything cosmetic left over from before the states jumped to
+    /// another point in time
+    fn resync_cosmetics(&mut self) {
+        self.pending_events.clear();
+        self.clock.reset();
+        self.audio
+            .resync(self.states.boss_alive(), self.states.all_heroes_out());
+        self.particles.clear();
+        self.popups.clear();
+    }
+
+    fn player_input_map(&mut self, player: u8) -> Result<&mut InputMap, String> {
+        self.input_maps
+            .get_mut(player as usize)
+            .ok_or_else(|| format!("no player {player}"))
+    }
+
+    pub fn stats(&self) -> &RunStats {
+        &self.states.stats
+    }
+
+    /// This run under `name`, with the settings it is played with
+    pub fn leaderboard_entry(&self, name: &str) -> LeaderboardEntry {
+        LeaderboardEntry {
+            name: name.to_string(),
+            score: self.states.score,
+            ticks: self.states.stats.ticks,
+            seed: self.settings.seed,
+            players: self.settings.players,
+            difficulty: self.settings.difficulty,
+            adaptive: self.settings.adaptive,
+            accessibility: self.settings.accessibility,
+        }
+    }
+
+    /// Keep the unlocked achievements in `store` instead, loading the ones
+    /// it already has
+    pub fn set_achievement_store(&mut self, store: Box<dyn AchievementStore>) {
+        self.achievements = Achievements::new(store);
+    }
+
+    pub fn is_unlocked(&self, achievement: &str) -> bool {
+        self.achievements.is_unlocked(achievement)
+    }
+
+    /// Hash of the state after every tick so far
+    pub fn checksums(&self) -> &[u64] {
+        &self.checksums
+    }
+
+    /// Native counterpart of [`Game::drain_events`]
+    pub fn take_events(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.pending_events)
+    }
+
+    /// Native counterpart of [`Game::drain_audio_cues`]
+    pub fn take_audio_cues(&mut self) -> Vec<AudioCue> {
+        self.audio.take_cues()
+    }
 }
@@ -0,0 +1,194 @@
+//! Leveled and categorized logging, with a pluggable sink
+//!
+//! Game logic logs through [`game_log!`](crate::game_log), which checks the
+//! filter before formatting anything, so disabled messages cost nearly nothing.
+
+use std::{cell::RefCell, fmt, str::FromStr};
+
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum LogLevel {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl FromStr for LogLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "off" => Ok(Self::Off),
+            "error" => Ok(Self::Error),
+            "warn" => Ok(Self::Warn),
+            "info" => Ok(Self::Info),
+            "debug" => Ok(Self::Debug),
+            "trace" => Ok(Self::Trace),
+            _ => Err(format!("unknown log level `{s}`")),
+        }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LogCategory {
+    Spawn,
+    Collision,
+    Scoring,
+    Input,
+    Net,
+    Director,
+}
+
+impl LogCategory {
+    fn mask(self) -> u8 {
+        1 << self as u8
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            LogCategory::Spawn => "spawn",
+            LogCategory::Collision => "collision",
+            LogCategory::Scoring => "scoring",
+            LogCategory::Input => "input",
+            LogCategory::Net => "net",
+            LogCategory::Director => "director",
+        }
+    }
+}
+
+/// Where the log messages that pass the filter end up
+pub trait LogSink {
+    fn write(&mut self, level: LogLevel, category: LogCategory, message: &str);
+}
+
+/// Forwards to the browser console, picking the console method by level
+#[cfg(target_arch = "wasm32")]
+pub struct ConsoleSink;
+
+#[cfg(target_arch = "wasm32")]
+impl LogSink for ConsoleSink {
+    fn write(&mut self, level: LogLevel, category: LogCategory, message: &str) {
+        use web_sys::console;
+
+        let message = JsValue::from_str(&format!("[{}] {message}", category.name()));
+        match level {
+            LogLevel::Error => console::error_1(&message),
+            LogLevel::Warn => console::warn_1(&message),
+            LogLevel::Info => console::info_1(&message),
+            _ => console::debug_1(&message),
+        }
+    }
+}
+
+/// Writes to stderr
+#[cfg(not(target_arch = "wasm32"))]
+pub struct StderrSink;
+
+#[cfg(not(target_arch = "wasm32"))]
+impl LogSink for StderrSink {
+    fn write(&mut self, level: LogLevel, category: LogCategory, message: &str) {
+        eprintln!("{level:?} [{}] {message}", category.name());
+    }
+}
+
+/// Writes to a file
+#[cfg(not(target_arch = "wasm32"))]
+pub struct FileSink(std::io::LineWriter<std::fs::File>);
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FileSink {
+    pub fn create(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        Ok(Self(std::io::LineWriter::new(std::fs::File::create(path)?)))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl LogSink for FileSink {
+    fn write(&mut self, level: LogLevel, category: LogCategory, message: &str) {
+        use std::io::Write;
+
+        // a log line failing to be written is not worth stopping the game for
+        let _ = writeln!(self.0, "{level:?} [{}] {message}", category.name());
+    }
+}
+
+struct Logger {
+    level: LogLevel,
+    /// bit set of the enabled [`LogCategory`]s
+    categories: u8,
+    sink: Box<dyn LogSink>,
+}
+
+impl Default for Logger {
+    fn default() -> Self {
+        Self {
+            level: LogLevel::Warn,
+            categories: u8::MAX,
+            #[cfg(target_arch = "wasm32")]
+            sink: Box::new(ConsoleSink),
+            #[cfg(not(target_arch = "wasm32"))]
+            sink: Box::new(StderrSink),
+        }
+    }
+}
+
+thread_local! {
+    static LOGGER: RefCell<Logger> = RefCell::new(Logger::default());
+}
+
+/// Messages less severe than `level` are dropped
+pub fn set_level(level: LogLevel) {
+    LOGGER.with(|logger| logger.borrow_mut().level = level);
+}
+
+pub fn set_category_enabled(category: LogCategory, enabled: bool) {
+    LOGGER.with(|logger| {
+        let mut logger = logger.borrow_mut();
+        if enabled {
+            logger.categories |= category.mask();
+        } else {
+            logger.categories &= !category.mask();
+        }
+    });
+}
+
+pub fn set_sink(sink: Box<dyn LogSink>) {
+    LOGGER.with(|logger| logger.borrow_mut().sink = sink);
+}
+
+pub fn enabled(level: LogLevel, category: LogCategory) -> bool {
+    LOGGER.with(|logger| {
+        let logger = logger.borrow();
+        level != LogLevel::Off && level <= logger.level && logger.categories & category.mask() != 0
+    })
+}
+
+/// Write to the sink without checking the filter, use [`game_log!`](crate::game_log) instead
+pub fn write(level: LogLevel, category: LogCategory, args: fmt::Arguments) {
+    LOGGER.with(|logger| {
+        logger
+            .borrow_mut()
+            .sink
+            .write(level, category, &args.to_string())
+    });
+}
+
+/// `game_log!(Debug, Collision, "bullet hit {}", name)`
+#[macro_export]
+macro_rules! game_log {
+    ($level:ident, $category:ident, $($arg:tt)+) => {
+        if $crate::log::enabled($crate::log::LogLevel::$level, $crate::log::LogCategory::$category) {
+            $crate::log::write(
+                $crate::log::LogLevel::$level,
+                $crate::log::LogCategory::$category,
+                format_args!($($arg)+),
+            );
+        }
+    };
+}
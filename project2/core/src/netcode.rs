@@ -0,0 +1,526 @@
+//! Rollback netcode for two peers.
+//!
+//! Both peers run the whole simulation. Local inputs are scheduled a few
+//! ticks ahead (the input delay) and sent to the other peer, whose inputs are
+//! predicted until they arrive. When a prediction turns out wrong, the state
+//! saved before that tick is restored and the following ticks are simulated
+//! again. Peers exchange checksums of the confirmed states to detect desyncs.
+//!
+//! Packets go through a [`Transport`], [`LoopbackTransport`] simulates a bad
+//! network in-process.
+
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, VecDeque},
+    rc::Rc,
+};
+
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg32;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game_log,
+    input::{Action, ActionEvent, UserInput},
+    GameSettings, GameStates,
+};
+
+/// Furthest the simulation runs ahead of the confirmed inputs, in ticks,
+/// the session stalls beyond it
+pub const MAX_ROLLBACK: u32 = 16;
+/// Ticks between two checksum exchanges
+pub const CHECKSUM_INTERVAL: u32 = 10;
+/// Checksums older than this many ticks are forgotten, unless compared
+const CHECKSUM_HISTORY: u32 = 256;
+
+/// Actions carried by an [`InputFrame`], in bit order
+const FRAME_ACTIONS: [Action; 7] = [
+    Action::MoveUp,
+    Action::MoveDown,
+    Action::MoveLeft,
+    Action::MoveRight,
+    Action::Fire,
+    Action::Bomb,
+    Action::Focus,
+];
+
+/// The actions a player holds during a tick, a bit per action
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct InputFrame(pub u8);
+
+impl InputFrame {
+    fn bit(action: Action) -> Option<u8> {
+        FRAME_ACTIONS
+            .iter()
+            .position(|&a| a == action)
+            .map(|position| 1 << position)
+    }
+
+    /// The frame with `action` held or released, actions the simulation
+    /// doesn't see (pause, rewind) are ignored
+    pub fn with(self, action: Action, held: bool) -> Self {
+        match Self::bit(action) {
+            Some(bit) if held => Self(self.0 | bit),
+            Some(bit) => Self(self.0 & !bit),
+            None => self,
+        }
+    }
+
+    pub fn held(self, action: Action) -> bool {
+        Self::bit(action).is_some_and(|bit| self.0 & bit != 0)
+    }
+
+    fn of(input: &UserInput) -> Self {
+        let held = [
+            input.up,
+            input.down,
+            input.left,
+            input.right,
+            input.fire,
+            input.bomb,
+            input.focus,
+        ];
+        FRAME_ACTIONS
+            .into_iter()
+            .zip(held)
+            .fold(Self::default(), |frame, (action, held)| {
+                frame.with(action, held)
+            })
+    }
+}
+
+/// Press and release what differs between the held actions of `player` and `frame`
+fn apply_frame(states: &mut GameStates, player: u8, frame: InputFrame) {
+    let current = InputFrame::of(&states.heroes[player as usize].input);
+    for action in FRAME_ACTIONS {
+        let pressed = frame.held(action);
+        if current.held(action) != pressed {
+            states.update(player, &ActionEvent { action, pressed });
+        }
+    }
+}
+
+/// Unreliable, unordered packet delivery to the other peer
+pub trait Transport {
+    /// Send a packet, it may be lost, delayed or reordered
+    fn send(&mut self, packet: Vec<u8>);
+    /// The next packet that arrived, if any
+    fn receive(&mut self) -> Option<Vec<u8>>;
+}
+
+/// How bad the simulated network of a [`LoopbackTransport`] is
+#[derive(Clone, Copy, Debug)]
+pub struct LinkConditions {
+    /// Minimum delivery delay, in ticks
+    pub latency: u32,
+    /// Extra random delay, up to this many ticks, which reorders packets
+    pub jitter: u32,
+    /// Probability of a packet to be dropped
+    pub loss: f32,
+}
+
+impl LinkConditions {
+    pub const PERFECT: Self = Self {
+        latency: 0,
+        jitter: 0,
+        loss: 0.0,
+    };
+}
+
+struct Link {
+    now: u32,
+    conditions: LinkConditions,
+    rng: Pcg32,
+    /// packets on their way to each side, with the tick they arrive on
+    in_flight: [Vec<(u32, Vec<u8>)>; 2],
+}
+
+/// One end of an in-process link, time on the link only passes with
+/// [`LoopbackTransport::advance`]
+pub struct LoopbackTransport {
+    link: Rc<RefCell<Link>>,
+    side: usize,
+}
+
+impl LoopbackTransport {
+    /// Both ends of a link, `seed` drives the losses and the jitter
+    pub fn pair(conditions: LinkConditions, seed: u64) -> (Self, Self) {
+        let link = Rc::new(RefCell::new(Link {
+            now: 0,
+            conditions,
+            rng: Pcg32::seed_from_u64(seed),
+            in_flight: [vec![], vec![]],
+        }));
+        (
+            Self {
+                link: link.clone(),
+                side: 0,
+            },
+            Self { link, side: 1 },
+        )
+    }
+
+    /// Let a tick pass on the link, for both ends
+    pub fn advance(&self) {
+        self.link.borrow_mut().now += 1;
+    }
+}
+
+impl Transport for LoopbackTransport {
+    fn send(&mut self, packet: Vec<u8>) {
+        let link = &mut *self.link.borrow_mut();
+        if link.rng.gen::<f32>() < link.conditions.loss {
+            return;
+        }
+        let arrival =
+            link.now + link.conditions.latency + link.rng.gen_range(0..=link.conditions.jitter);
+        link.in_flight[1 - self.side].push((arrival, packet));
+    }
+
+    fn receive(&mut self) -> Option<Vec<u8>> {
+        let link = &mut *self.link.borrow_mut();
+        let now = link.now;
+        let in_flight = &mut link.in_flight[self.side];
+        let index = in_flight
+            .iter()
+            .enumerate()
+            .filter(|(_, (arrival, _))| *arrival <= now)
+            .min_by_key(|(_, (arrival, _))| *arrival)
+            .map(|(index, _)| index)?;
+        Some(in_flight.remove(index).1)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Message {
+    /// The frames of the sender from tick `start` on, and how many frames of
+    /// the receiver it has, so that only those are sent again
+    Input {
+        start: u32,
+        frames: Vec<InputFrame>,
+        ack: u32,
+    },
+    /// Checksum of the sender's confirmed state at the start of `tick`
+    Checksum { tick: u32, checksum: u64 },
+}
+
+/// The peers disagree on the state at the start of `tick`
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Desync {
+    pub tick: u32,
+    pub local: u64,
+    pub remote: u64,
+}
+
+/// One peer of a two player game, see the [module](self) documentation
+pub struct RollbackSession<T: Transport> {
+    settings: GameSettings,
+    states: GameStates,
+    local_player: u8,
+    input_delay: u32,
+    transport: T,
+    /// Known frames of each player, by tick
+    inputs: [BTreeMap<u32, InputFrame>; 2],
+    /// Frames of each player known for every tick before this one
+    received: [u32; 2],
+    /// Local frames the other peer has
+    acked: u32,
+    /// Remote frames used without knowing them, by tick
+    predicted: BTreeMap<u32, InputFrame>,
+    /// Earliest tick simulated with a wrong prediction
+    mispredicted: Option<u32>,
+    /// States at the start of the last ticks, the newest is the one before `states`
+    snapshots: VecDeque<GameStates>,
+    /// Ticks up to this one had their checksum compared
+    checked: u32,
+    checksums: BTreeMap<u32, u64>,
+    remote_checksums: BTreeMap<u32, u64>,
+    desync: Option<Desync>,
+    rollbacks: u32,
+}
+
+impl<T: Transport> RollbackSession<T> {
+    /// `settings` must be the same on both peers, `local_player` is 0 on one
+    /// of them and 1 on the other. Local inputs are applied `input_delay`
+    /// ticks after they are given, the longer, the fewer rollbacks.
+    pub fn new(settings: GameSettings, local_player: u8, input_delay: u32, transport: T) -> Self {
+        let settings = GameSettings {
+            players: 2,
+            ..settings
+        };
+        let mut inputs = [BTreeMap::new(), BTreeMap::new()];
+        inputs[local_player as usize] = (0..input_delay)
+            .map(|tick| (tick, InputFrame::default()))
+            .collect();
+        let mut received = [0; 2];
+        received[local_player as usize] = input_delay;
+        Self {
+            states: GameStates::new(&settings),
+            settings,
+            local_player,
+            input_delay,
+            transport,
+            inputs,
+            received,
+            acked: 0,
+            predicted: BTreeMap::new(),
+            mispredicted: None,
+            snapshots: VecDeque::new(),
+            checked: 0,
+            checksums: BTreeMap::new(),
+            remote_checksums: BTreeMap::new(),
+            desync: None,
+            rollbacks: 0,
+        }
+    }
+
+    fn remote_player(&self) -> u8 {
+        1 - self.local_player
+    }
+
+    /// Ticks simulated so far
+    pub fn current_tick(&self) -> u32 {
+        self.states.stats.ticks
+    }
+
+    /// Inputs of both players are known for every tick before this one,
+    /// the states up to it are final
+    pub fn confirmed_tick(&self) -> u32 {
+        self.received[0].min(self.received[1])
+    }
+
+    /// The current, possibly predicted, state
+    pub fn states(&self) -> &GameStates {
+        &self.states
+    }
+
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// First checksum mismatch found, the session goes on regardless
+    pub fn desync(&self) -> Option<Desync> {
+        self.desync
+    }
+
+    /// How many times the state was restored because of a wrong prediction
+    pub fn rollbacks(&self) -> u32 {
+        self.rollbacks
+    }
+
+    /// Give the actions held by the local player now, to be applied
+    /// `input_delay` ticks later. Call it once before every [`Self::tick`].
+    pub fn add_local_input(&mut self, frame: InputFrame) {
+        let local = self.local_player as usize;
+        let target = self.current_tick() + self.input_delay;
+        while self.received[local] <= target {
+            self.inputs[local].insert(self.received[local], frame);
+            self.received[local] += 1;
+        }
+    }
+
+    /// Exchange packets and advance one tick, unless too far ahead of the
+    /// other peer. Returns whether the simulation advanced.
+    pub fn tick(&mut self) -> bool {
+        while let Some(packet) = self.transport.receive() {
+            match serde_json::from_slice(&packet) {
+                Ok(message) => self.handle(message),
+                Err(err) => game_log!(Warn, Net, "invalid packet: {err}"),
+            }
+        }
+        if let Some(tick) = self.mispredicted.take() {
+            self.rollback(tick);
+        }
+        // a missed local input holds the previous one
+        let local = self.local_player as usize;
+        if self.received[local] <= self.current_tick() {
+            let last = self.inputs[local].values().next_back().copied();
+            self.add_local_input(last.unwrap_or_default());
+        }
+
+        let advanced = self.current_tick() < self.confirmed_tick() + MAX_ROLLBACK;
+        if advanced {
+            self.snapshots.push_back(self.states.clone());
+            // one more than the rollback window, for the checksums
+            while self.snapshots.len() > MAX_ROLLBACK as usize + 1 {
+                self.snapshots.pop_front();
+            }
+            self.simulate();
+        } else {
+            game_log!(
+                Debug,
+                Net,
+                "waiting for the other peer at tick {}",
+                self.current_tick()
+            );
+        }
+
+        self.check_confirmed();
+        self.send_inputs();
+        self.prune();
+        advanced
+    }
+
+    fn handle(&mut self, message: Message) {
+        match message {
+            Message::Input { start, frames, ack } => {
+                self.acked = self.acked.max(ack);
+                let remote = self.remote_player() as usize;
+                if start > self.received[remote] {
+                    // can't happen with acks, but never leave a hole
+                    return;
+                }
+                let new_frames = frames
+                    .into_iter()
+                    .zip(start..)
+                    .skip((self.received[remote] - start) as usize);
+                for (frame, tick) in new_frames {
+                    if let Some(predicted) = self.predicted.remove(&tick) {
+                        if predicted != frame {
+                            self.mispredicted = Some(
+                                self.mispredicted
+                                    .map_or(tick, |earliest| earliest.min(tick)),
+                            );
+                        }
+                    }
+                    self.inputs[remote].insert(tick, frame);
+                    self.received[remote] = tick + 1;
+                }
+            }
+            Message::Checksum { tick, checksum } => {
+                self.remote_checksums.insert(tick, checksum);
+                self.compare_checksums(tick);
+            }
+        }
+    }
+
+    /// The frame of the remote player at `tick`, the last known one if it hasn't arrived
+    fn remote_frame(&mut self, tick: u32) -> InputFrame {
+        let inputs = &self.inputs[self.remote_player() as usize];
+        if let Some(frame) = inputs.get(&tick) {
+            return *frame;
+        }
+        let frame = inputs
+            .range(..tick)
+            .next_back()
+            .map(|(_, frame)| *frame)
+            .unwrap_or_default();
+        self.predicted.insert(tick, frame);
+        frame
+    }
+
+    fn simulate(&mut self) {
+        let tick = self.current_tick();
+        let (local_player, remote_player) = (self.local_player, self.remote_player());
+        let local = self.inputs[local_player as usize][&tick];
+        let remote = self.remote_frame(tick);
+        apply_frame(&mut self.states, local_player, local);
+        apply_frame(&mut self.states, remote_player, remote);
+        self.states.tick(&self.settings);
+    }
+
+    /// Restore the state at the start of `tick` and simulate up to now again
+    fn rollback(&mut self, tick: u32) {
+        let now = self.current_tick();
+        let oldest = now - self.snapshots.len() as u32;
+        let index = tick.checked_sub(oldest).map(|index| index as usize);
+        let Some((index, states)) =
+            index.and_then(|index| Some((index, self.snapshots.get(index)?)))
+        else {
+            // the window guarantees it never happens
+            game_log!(Error, Net, "no snapshot to roll back to tick {tick}");
+            return;
+        };
+        self.states = states.clone();
+        self.snapshots.truncate(index);
+        self.predicted.retain(|&predicted, _| predicted < tick);
+        game_log!(Debug, Net, "rolling back from tick {now} to {tick}");
+        self.rollbacks += 1;
+        while self.current_tick() < now {
+            self.snapshots.push_back(self.states.clone());
+            self.simulate();
+        }
+    }
+
+    /// Checksum the states that became final and send them to the other peer
+    fn check_confirmed(&mut self) {
+        let last = self.confirmed_tick().min(self.current_tick());
+        let oldest = self.current_tick() - self.snapshots.len() as u32;
+        for tick in self.checked..=last {
+            if tick % CHECKSUM_INTERVAL != 0 || self.checksums.contains_key(&tick) {
+                continue;
+            }
+            let states = if tick == self.current_tick() {
+                &self.states
+            } else {
+                let snapshot = tick
+                    .checked_sub(oldest)
+                    .and_then(|index| self.snapshots.get(index as usize));
+                let Some(states) = snapshot else {
+                    // the window guarantees it never happens
+                    game_log!(Error, Net, "no snapshot to checksum tick {tick}");
+                    continue;
+                };
+                states
+            };
+            let checksum = states.checksum();
+            self.checksums.insert(tick, checksum);
+            self.send(&Message::Checksum { tick, checksum });
+            self.compare_checksums(tick);
+        }
+        self.checked = last;
+    }
+
+    fn compare_checksums(&mut self, tick: u32) {
+        let (Some(&local), Some(&remote)) =
+            (self.checksums.get(&tick), self.remote_checksums.get(&tick))
+        else {
+            return;
+        };
+        if local != remote && self.desync.is_none() {
+            game_log!(
+                Error,
+                Net,
+                "desync at tick {tick}: {local:016x} != {remote:016x}"
+            );
+            self.desync = Some(Desync {
+                tick,
+                local,
+                remote,
+            });
+        }
+    }
+
+    fn send_inputs(&mut self) {
+        let local = self.local_player as usize;
+        let frames = self.inputs[local]
+            .range(self.acked..)
+            .map(|(_, frame)| *frame)
+            .collect();
+        self.send(&Message::Input {
+            start: self.acked,
+            frames,
+            ack: self.received[1 - local],
+        });
+    }
+
+    fn send(&mut self, message: &Message) {
+        self.transport.send(serde_json::to_vec(message).unwrap());
+    }
+
+    /// Forget what no rollback or checksum will need anymore
+    fn prune(&mut self) {
+        let confirmed = self.confirmed_tick().min(self.current_tick());
+        let local = self.local_player as usize;
+        let remote = 1 - local;
+        // the last confirmed frame is kept for the predictions
+        self.inputs[remote] = self.inputs[remote].split_off(&confirmed.saturating_sub(1));
+        let keep_local = self.acked.min(confirmed).saturating_sub(1);
+        self.inputs[local] = self.inputs[local].split_off(&keep_local);
+        let oldest_checksum = self.checked.saturating_sub(CHECKSUM_HISTORY);
+        self.checksums = self.checksums.split_off(&oldest_checksum);
+        self.remote_checksums = self.remote_checksums.split_off(&oldest_checksum);
+    }
+}
@@ -0,0 +1,259 @@
+//! Purely cosmetic particles: explosions, debris, muzzle flashes, engine trails...
+//!
+//! Particles never affect the simulation. They use their own RNG so that
+//! emitting more or less of them can't change what happens in the game.
+
+use std::f32::consts::PI;
+
+use nalgebra::Vector2;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg32;
+
+use crate::{
+    entity::enemy::EnemyKind,
+    event::GameEvent,
+    render::{BitmapAsset, Primitive},
+};
+
+/// Particles emitted while this many are alive are dropped
+pub const MAX_PARTICLES: usize = 512;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EmitterKind {
+    Explosion,
+    Debris,
+    MuzzleFlash,
+    EngineTrail,
+    PickupSparkle,
+}
+
+/// How an emitter spawns its particles, ranges are `(min, max)`
+struct EmitterSpec {
+    bitmap: BitmapAsset,
+    count: u32,
+    speed: (f32, f32),
+    /// emission direction and half the cone width, in rad
+    direction: f32,
+    spread: f32,
+    lifetime: (u32, u32),
+    /// added to the vertical speed every tick
+    gravity: f32,
+    /// scale at birth and at death
+    scale: (f32, f32),
+    fade: bool,
+    spin: f32,
+}
+
+impl EmitterKind {
+    fn spec(self) -> EmitterSpec {
+        match self {
+            EmitterKind::Explosion => EmitterSpec {
+                bitmap: BitmapAsset::BulletEnemy,
+                count: 12,
+                speed: (2.0, 6.0),
+                direction: 0.0,
+                spread: PI,
+                lifetime: (6, 12),
+                gravity: 0.0,
+                scale: (1.5, 0.2),
+                fade: true,
+                spin: 0.0,
+            },
+            EmitterKind::Debris => EmitterSpec {
+                bitmap: BitmapAsset::Enemy1Down3,
+                count: 4,
+                speed: (1.0, 4.0),
+                direction: PI / 2.0,
+                spread: PI / 2.0,
+                lifetime: (10, 20),
+                gravity: -0.4,
+                scale: (0.3, 0.15),
+                fade: true,
+                spin: 0.3,
+            },
+            EmitterKind::MuzzleFlash => EmitterSpec {
+                bitmap: BitmapAsset::BulletPlayer,
+                count: 3,
+                speed: (1.0, 3.0),
+                direction: PI / 2.0,
+                spread: PI / 6.0,
+                lifetime: (2, 3),
+                gravity: 0.0,
+                scale: (1.2, 0.4),
+                fade: true,
+                spin: 0.0,
+            },
+            EmitterKind::EngineTrail => EmitterSpec {
+                bitmap: BitmapAsset::BulletPlayer,
+                count: 1,
+                speed: (2.0, 3.0),
+                direction: -PI / 2.0,
+                spread: PI / 12.0,
+                lifetime: (4, 6),
+                gravity: 0.0,
+                scale: (0.8, 0.1),
+                fade: true,
+                spin: 0.0,
+            },
+            EmitterKind::PickupSparkle => EmitterSpec {
+                bitmap: BitmapAsset::BulletPlayer,
+                count: 8,
+                speed: (0.5, 2.0),
+                direction: 0.0,
+                spread: PI,
+                lifetime: (8, 14),
+                gravity: 0.1,
+                scale: (0.8, 0.2),
+                fade: true,
+                spin: 0.2,
+            },
+        }
+    }
+}
+
+struct Particle {
+    bitmap: BitmapAsset,
+    pos: Vector2<f32>,
+    speed: Vector2<f32>,
+    gravity: f32,
+    age: u32,
+    lifetime: u32,
+    scale: (f32, f32),
+    fade: bool,
+    rotation: f32,
+    spin: f32,
+}
+
+impl Particle {
+    /// 0 at birth, 1 at death
+    fn progress(&self) -> f32 {
+        self.age as f32 / self.lifetime as f32
+    }
+
+    fn render(&self, ms_delta: u128) -> Primitive {
+        let predicted_pos = self.pos + (self.speed / 50.0) * ms_delta as f32;
+        let t = self.progress();
+        let scale = self.scale.0 + (self.scale.1 - self.scale.0) * t;
+        let alpha = if self.fade { 1.0 - t } else { 1.0 };
+
+        Primitive::new(
+            self.bitmap,
+            (predicted_pos.x, predicted_pos.y),
+            self.rotation,
+        )
+        .with_scale(scale)
+        .with_alpha(alpha)
+    }
+}
+
+pub struct ParticleSystem {
+    particles: Vec<Particle>,
+    rng: Pcg32,
+    /// Off for players sensitive to flashes, see [`ParticleSystem::set_flashes`]
+    flashes: bool,
+}
+
+impl ParticleSystem {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            particles: vec![],
+            rng: Pcg32::seed_from_u64(seed),
+            flashes: true,
+        }
+    }
+
+    /// Whether to emit the flashing particles, the muzzle flashes
+    pub fn set_flashes(&mut self, flashes: bool) {
+        self.flashes = flashes;
+    }
+
+    pub fn len(&self) -> usize {
+        self.particles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.particles.is_empty()
+    }
+
+    /// Remove every live particle
+    pub fn clear(&mut self) {
+        self.particles.clear();
+    }
+
+    pub fn emit(&mut self, kind: EmitterKind, pos: (f32, f32)) {
+        self.emit_scaled(kind, pos, 1);
+    }
+
+    /// Emit `multiplier` times the usual particle count
+    fn emit_scaled(&mut self, kind: EmitterKind, pos: (f32, f32), multiplier: u32) {
+        if !self.flashes && matches!(kind, EmitterKind::MuzzleFlash) {
+            return;
+        }
+        let spec = kind.spec();
+        for _ in 0..spec.count * multiplier {
+            if self.particles.len() >= MAX_PARTICLES {
+                return;
+            }
+            let angle = spec.direction + self.rng.gen_range(-spec.spread..=spec.spread);
+            let speed = self.rng.gen_range(spec.speed.0..=spec.speed.1);
+            let spin = self.rng.gen_range(-spec.spin..=spec.spin);
+            self.particles.push(Particle {
+                bitmap: spec.bitmap,
+                pos: Vector2::new(pos.0, pos.1),
+                speed: Vector2::new(angle.cos(), angle.sin()) * speed,
+                gravity: spec.gravity,
+                age: 0,
+                lifetime: self.rng.gen_range(spec.lifetime.0..=spec.lifetime.1),
+                scale: spec.scale,
+                fade: spec.fade,
+                rotation: if spec.spin > 0.0 {
+                    self.rng.gen_range(0.0..2.0 * PI)
+                } else {
+                    0.0
+                },
+                spin,
+            });
+        }
+    }
+
+    /// Emit the particles that go with the events of a tick
+    pub fn handle_events(&mut self, events: &[GameEvent]) {
+        for event in events {
+            match *event {
+                GameEvent::EnemyKilled { kind, pos, .. } => {
+                    let size = match kind {
+                        EnemyKind::MiddleCup => 1,
+                        EnemyKind::BigCup => 2,
+                        EnemyKind::SuperBigCup => 4,
+                    };
+                    self.emit_scaled(EmitterKind::Explosion, pos, size);
+                }
+                GameEvent::AnimationEvent {
+                    name: "debris",
+                    pos,
+                } => self.emit(EmitterKind::Debris, pos),
+                GameEvent::HeroHit { pos, .. } => self.emit(EmitterKind::Explosion, pos),
+                GameEvent::BulletFired { pos, .. } => self.emit(EmitterKind::MuzzleFlash, pos),
+                GameEvent::PickupCollected { pos } => self.emit(EmitterKind::PickupSparkle, pos),
+                _ => (),
+            }
+        }
+    }
+
+    pub fn tick(&mut self) {
+        for particle in self.particles.iter_mut() {
+            particle.age += 1;
+            particle.speed.y += particle.gravity;
+            particle.pos += particle.speed;
+            particle.rotation += particle.spin;
+        }
+        self.particles
+            .retain(|particle| particle.age < particle.lifetime);
+    }
+
+    pub fn render(&self, ms_delta: u128) -> impl Iterator<Item = Primitive> + '_ {
+        self.particles
+            .iter()
+            .map(move |particle| particle.render(ms_delta))
+    }
+}
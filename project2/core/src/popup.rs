@@ -0,0 +1,119 @@
+//! Floating score popups, cosmetic like the particles: the points of every
+//! [`GameEvent::ScoreAwarded`] rise from where they were earned and fade out
+
+use nalgebra::Vector2;
+
+use crate::{
+    clock::MS_PER_TICK,
+    entity::hero::player_tint,
+    event::GameEvent,
+    render::{BitmapAsset, Primitive},
+    scoring::ScoreReason,
+};
+
+const DIGITS: [BitmapAsset; 10] = [
+    BitmapAsset::Digit0,
+    BitmapAsset::Digit1,
+    BitmapAsset::Digit2,
+    BitmapAsset::Digit3,
+    BitmapAsset::Digit4,
+    BitmapAsset::Digit5,
+    BitmapAsset::Digit6,
+    BitmapAsset::Digit7,
+    BitmapAsset::Digit8,
+    BitmapAsset::Digit9,
+];
+
+/// Ticks a popup stays on screen
+const LIFETIME: u32 = 30;
+/// Pixels per tick
+const RISE_SPEED: f32 = 1.5;
+/// Oldest popups are dropped beyond that
+const MAX_POPUPS: usize = 64;
+
+struct Popup {
+    points: u32,
+    pos: Vector2<f32>,
+    scale: f32,
+    tint: u32,
+    age: u32,
+}
+
+impl Popup {
+    fn render(&self, ms_delta: u128) -> impl Iterator<Item = Primitive> {
+        let t = self.age as f32 / LIFETIME as f32;
+        let rise = RISE_SPEED * (self.age as f32 + ms_delta as f32 / MS_PER_TICK as f32);
+        // neighbouring digits share their outline
+        let advance = (BitmapAsset::Digit0.info().size.0 - 1) as f32 * self.scale;
+        let digits = self.points.to_string();
+        let left = self.pos.x - advance * (digits.len() - 1) as f32 / 2.0;
+        let (y, scale, tint) = (self.pos.y + rise, self.scale, self.tint);
+        digits
+            .into_bytes()
+            .into_iter()
+            .enumerate()
+            .map(move |(i, digit)| {
+                Primitive::new(
+                    DIGITS[(digit - b'0') as usize],
+                    (left + advance * i as f32, y),
+                    0.0,
+                )
+                .with_scale(scale)
+                .with_alpha(1.0 - t * t)
+                .with_tint(tint)
+            })
+    }
+}
+
+#[derive(Default)]
+pub struct ScorePopups {
+    popups: Vec<Popup>,
+}
+
+impl ScorePopups {
+    pub fn clear(&mut self) {
+        self.popups.clear();
+    }
+
+    pub fn handle_events(&mut self, events: &[GameEvent]) {
+        for event in events {
+            if let GameEvent::ScoreAwarded {
+                player,
+                points,
+                reason,
+                pos,
+            } = *event
+            {
+                let scale = match reason {
+                    ScoreReason::Kill => 1.0,
+                    ScoreReason::Graze => 0.5,
+                    ScoreReason::WaveBonus => 1.5,
+                };
+                self.popups.push(Popup {
+                    points,
+                    pos: Vector2::new(pos.0, pos.1),
+                    scale,
+                    tint: player_tint(player),
+                    age: 0,
+                });
+            }
+        }
+        if self.popups.len() > MAX_POPUPS {
+            let overflow = self.popups.len() - MAX_POPUPS;
+            self.popups.drain(..overflow);
+        }
+    }
+
+    pub fn tick(&mut self) {
+        for popup in self.popups.iter_mut() {
+            popup.age += 1;
+        }
+        self.popups.retain(|popup| popup.age < LIFETIME);
+    }
+
+    pub fn render(&self, ms_delta: u128) -> impl Iterator<Item = Primitive> + '_ {
+        self.popups
+            .iter()
+            .flat_map(move |popup| popup.render(ms_delta))
+    }
+}
@@ -0,0 +1,224 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    accessibility::Accessibility,
+    difficulty::DifficultyPreset,
+    headless,
+    input::{Action, AnalogInputEvent, PointerEvent, PointerPhase, UserInputEvent},
+    Game, GameSettings,
+};
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RecordedInput {
+    Action {
+        /// Absent in single player replays
+        #[serde(default)]
+        player: u8,
+        action: Action,
+        pressed: bool,
+    },
+    /// Raw key, translated by the input map of the game it is applied to
+    Key { key: String, pressed: bool },
+    Pointer {
+        #[serde(default)]
+        player: u8,
+        phase: PointerPhase,
+        x: f32,
+        y: f32,
+    },
+    /// Stick position, after the stick settings of the recording game
+    Analog {
+        #[serde(default)]
+        player: u8,
+        x: f32,
+        y: f32,
+    },
+}
+
+/// A recorded input event, applied right before the tick it belongs to
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ReplayInput {
+    pub tick: u32,
+    #[serde(flatten)]
+    pub input: RecordedInput,
+}
+
+impl ReplayInput {
+    pub fn key(tick: u32, key: &str, pressed: bool) -> Self {
+        Self {
+            tick,
+            input: RecordedInput::Key {
+                key: key.to_string(),
+                pressed,
+            },
+        }
+    }
+
+    /// Feed the input to `game`
+    pub fn apply(&self, game: &mut Game) {
+        match &self.input {
+            RecordedInput::Action {
+                player,
+                action,
+                pressed,
+            } => game.action(*player, *action, *pressed),
+            RecordedInput::Key { key, pressed } => {
+                game.update(&UserInputEvent::new(key.clone(), *pressed))
+            }
+            RecordedInput::Pointer {
+                player,
+                phase,
+                x,
+                y,
+            } => game.update_pointer(*player, &PointerEvent::new(*phase, *x, *y)),
+            RecordedInput::Analog { player, x, y } => {
+                game.analog(*player, &AnalogInputEvent::new(*x, *y))
+            }
+        }
+    }
+}
+
+fn one() -> u8 {
+    1
+}
+
+/// Everything needed to reproduce a run: the seed, the play area, the players and the inputs
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    pub width: u16,
+    pub height: u16,
+    #[serde(default = "one")]
+    pub players: u8,
+    #[serde(default = "one")]
+    pub lives: u8,
+    #[serde(default)]
+    pub difficulty: DifficultyPreset,
+    #[serde(default)]
+    pub adaptive: bool,
+    #[serde(default)]
+    pub accessibility: Accessibility,
+    pub inputs: Vec<ReplayInput>,
+    /// Hash of the state after every tick of the recording, what [`Replay::verify`]
+    /// checks against
+    #[serde(default)]
+    pub checksums: Vec<u64>,
+}
+
+/// Where a replayed run first differs from its recording
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize)]
+pub struct Divergence {
+    /// The tick whose simulation gave another state, counting from 0 like
+    /// the input ticks
+    pub tick: u32,
+    pub expected: u64,
+    /// `None` if the replayed game was already over
+    pub actual: Option<u64>,
+}
+
+impl Replay {
+    /// The settings of the recorded game
+    pub fn settings(&self) -> GameSettings {
+        let mut settings = GameSettings::new(self.width, self.height);
+        settings.seed = self.seed;
+        settings.players = self.players;
+        settings.lives = self.lives;
+        settings.difficulty = self.difficulty;
+        settings.adaptive = self.adaptive;
+        settings.accessibility = self.accessibility;
+        settings
+    }
+
+    /// Play the inputs again and compare the state after every tick with the
+    /// recorded checksums, returns how many ticks were verified
+    pub fn verify(&self) -> Result<u32, Divergence> {
+        let mut game = Game::new(self.settings());
+        let ticks = self.checksums.len() as u32;
+        let mut divergence = None;
+        headless::run(&mut game, &self.inputs, ticks, |game| {
+            let tick = game.checksums().len() - 1;
+            let actual = game.checksums()[tick];
+            if divergence.is_none() && actual != self.checksums[tick] {
+                divergence = Some(Divergence {
+                    tick: tick as u32,
+                    expected: self.checksums[tick],
+                    actual: Some(actual),
+                });
+            }
+        });
+        if let Some(divergence) = divergence {
+            return Err(divergence);
+        }
+        let replayed = game.checksums().len();
+        match self.checksums.get(replayed) {
+            Some(&expected) => Err(Divergence {
+                tick: replayed as u32,
+                expected,
+                actual: None,
+            }),
+            None => Ok(ticks),
+        }
+    }
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|err| format!("invalid replay: {err}"))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+}
+
+/// Parse a plain text input script.
+///
+/// Every non-empty line is `<tick> <key> <down|up>`, or
+/// `<tick> pointer <down|move|up> <x> <y>` for mouse and touch,
+/// `#` starts a comment, and the space key is written as `space`.
+pub fn parse_script(script: &str) -> Result<Vec<ReplayInput>, String> {
+    let mut inputs = vec![];
+    for (line_no, line) in script.lines().enumerate() {
+        let line = line.split('#').next().unwrap().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let err = |msg: &str| format!("line {}: {msg}", line_no + 1);
+
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let input = match parts[..] {
+            [tick, "pointer", phase, x, y] => {
+                let phase = match phase {
+                    "down" => PointerPhase::Down,
+                    "move" => PointerPhase::Move,
+                    "up" => PointerPhase::Up,
+                    _ => return Err(err("phase must be `down`, `move` or `up`")),
+                };
+                ReplayInput {
+                    tick: tick.parse().map_err(|_| err("invalid tick"))?,
+                    input: RecordedInput::Pointer {
+                        player: 0,
+                        phase,
+                        x: x.parse().map_err(|_| err("invalid x"))?,
+                        y: y.parse().map_err(|_| err("invalid y"))?,
+                    },
+                }
+            }
+            [tick, key, action] => {
+                let key = match key {
+                    "space" => " ",
+                    key => key,
+                };
+                let pressed = match action {
+                    "down" => true,
+                    "up" => false,
+                    _ => return Err(err("action must be `down` or `up`")),
+                };
+                let tick = tick.parse().map_err(|_| err("invalid tick"))?;
+                ReplayInput::key(tick, key, pressed)
+            }
+            _ => return Err(err("expected `<tick> <key> <down|up>`")),
+        };
+        inputs.push(input);
+    }
+    inputs.sort_by_key(|input| input.tick);
+    Ok(inputs)
+}
@@ -0,0 +1,103 @@
+//! Recent history of the run, to step back a few seconds
+
+use std::collections::VecDeque;
+
+use wasm_bindgen::prelude::*;
+
+use crate::GameStates;
+
+/// How much history is kept, memory use is about `capacity` copies of the
+/// game state
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RewindSettings {
+    /// Ticks between two snapshots, rewinding re-simulates at most this many ticks
+    pub interval: u32,
+    /// Snapshots kept, 0 disables rewinding
+    pub capacity: u32,
+}
+
+impl Default for RewindSettings {
+    /// 10 seconds, a snapshot every second
+    fn default() -> Self {
+        Self {
+            interval: 20,
+            capacity: 10,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl RewindSettings {
+    #[wasm_bindgen(constructor)]
+    pub fn new(interval: u32, capacity: u32) -> Self {
+        Self {
+            interval: interval.max(1),
+            capacity,
+        }
+    }
+}
+
+/// Ring buffer of periodic snapshots, oldest first
+pub(crate) struct History {
+    settings: RewindSettings,
+    snapshots: VecDeque<GameStates>,
+}
+
+impl History {
+    pub fn new(settings: RewindSettings) -> Self {
+        Self {
+            settings,
+            snapshots: VecDeque::new(),
+        }
+    }
+
+    pub fn settings(&self) -> RewindSettings {
+        self.settings
+    }
+
+    /// Keep `states` if it falls on the snapshot interval
+    pub fn record(&mut self, states: &GameStates) {
+        if self.settings.capacity == 0
+            || !states
+                .stats
+                .ticks
+                .is_multiple_of(self.settings.interval.max(1))
+        {
+            return;
+        }
+        // re-simulated ticks are recorded again
+        self.snapshots
+            .retain(|snapshot| snapshot.stats.ticks < states.stats.ticks);
+        self.snapshots.push_back(states.clone());
+        while self.snapshots.len() > self.settings.capacity as usize {
+            self.snapshots.pop_front();
+        }
+    }
+
+    /// Forget everything and start over from `states`
+    pub fn reset(&mut self, settings: RewindSettings, states: &GameStates) {
+        self.settings = settings;
+        self.snapshots.clear();
+        if settings.capacity > 0 {
+            self.snapshots.push_back(states.clone());
+        }
+    }
+
+    /// Tick of the oldest state that can be restored
+    pub fn oldest_tick(&self) -> Option<u32> {
+        self.snapshots.front().map(|snapshot| snapshot.stats.ticks)
+    }
+
+    /// The latest snapshot at or before `tick`, dropping the newer ones
+    pub fn restore(&mut self, tick: u32) -> Option<GameStates> {
+        while self
+            .snapshots
+            .back()
+            .is_some_and(|snapshot| snapshot.stats.ticks > tick)
+        {
+            self.snapshots.pop_back();
+        }
+        self.snapshots.back().cloned()
+    }
+}
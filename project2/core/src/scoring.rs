@@ -0,0 +1,156 @@
+//! Points: what each kind of enemy is worth, kill chains, grazes and the
+//! bonus at the end of every wave
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    entity::{enemy::EnemyKind, hero::Hero},
+    event::GameEvent,
+    game_log,
+};
+
+/// Ticks a chain survives without a kill, 3 seconds
+pub const CHAIN_WINDOW: u32 = 60;
+/// Kills in a chain for each step of the multiplier
+pub const CHAIN_STEP: u32 = 5;
+pub const MAX_MULTIPLIER: u32 = 8;
+/// Points of an enemy bullet passing close to a hero, see [`GameEvent::NearMiss`]
+pub const GRAZE_POINTS: u32 = 50;
+/// Points per enemy of the wave that ended, for every hero still in the game
+pub const WAVE_BONUS: u32 = 100;
+
+/// Points of a kill before the chain multiplier
+pub fn kill_points(kind: EnemyKind) -> u32 {
+    match kind {
+        EnemyKind::MiddleCup => 100,
+        EnemyKind::BigCup => 600,
+        EnemyKind::SuperBigCup => 10000,
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoreReason {
+    Kill,
+    Graze,
+    WaveBonus,
+}
+
+/// Consecutive kills of a player, each within [`CHAIN_WINDOW`] of the previous one
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub struct Chain {
+    pub kills: u32,
+    /// Ticks left to extend the chain
+    pub timer: u32,
+}
+
+impl Chain {
+    pub fn multiplier(&self) -> u32 {
+        (1 + self.kills.saturating_sub(1) / CHAIN_STEP).min(MAX_MULTIPLIER)
+    }
+
+    fn kill(&mut self) -> u32 {
+        self.kills += 1;
+        self.timer = CHAIN_WINDOW;
+        self.multiplier()
+    }
+
+    fn tick(&mut self) {
+        self.timer = self.timer.saturating_sub(1);
+        if self.timer == 0 {
+            self.kills = 0;
+        }
+    }
+}
+
+/// Turns the events of every tick into points
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Scoring {
+    /// Indexed by player
+    chains: Vec<Chain>,
+    /// The wave in progress, rewarded when the next one starts
+    wave: u32,
+}
+
+impl Scoring {
+    pub fn chain(&self, player: u8) -> Chain {
+        self.chains
+            .get(player as usize)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn chain_mut(&mut self, player: u8) -> &mut Chain {
+        let player = player as usize;
+        if self.chains.len() <= player {
+            self.chains.resize(player + 1, Chain::default());
+        }
+        &mut self.chains[player]
+    }
+
+    /// Credit the heroes for the events of a tick: applies the chain
+    /// multiplier to the score of the kills, breaks the chain of the heroes
+    /// hit, and pushes a [`GameEvent::ScoreAwarded`] for every award.
+    /// Returns the points awarded in total.
+    pub fn award(&mut self, events: &mut Vec<GameEvent>, heroes: &mut [Hero]) -> u32 {
+        for chain in self.chains.iter_mut() {
+            chain.tick();
+        }
+
+        let mut awards = vec![];
+        for event in events.iter_mut() {
+            match event {
+                GameEvent::EnemyKilled {
+                    pos,
+                    score,
+                    player: Some(player),
+                    ..
+                } => {
+                    *score *= self.chain_mut(*player).kill();
+                    awards.push((*player, *score, ScoreReason::Kill, *pos));
+                }
+                GameEvent::HeroHit { player, .. } => {
+                    *self.chain_mut(*player) = Chain::default();
+                }
+                GameEvent::NearMiss { player, pos } => {
+                    awards.push((*player, GRAZE_POINTS, ScoreReason::Graze, *pos));
+                }
+                GameEvent::WaveStarted { wave } => {
+                    if self.wave > 0 {
+                        for hero in heroes.iter().filter(|hero| !hero.out()) {
+                            let pos = hero.motion_state.pos;
+                            awards.push((
+                                hero.player,
+                                WAVE_BONUS * self.wave,
+                                ScoreReason::WaveBonus,
+                                (pos.x, pos.y),
+                            ));
+                        }
+                    }
+                    self.wave = *wave;
+                }
+                _ => (),
+            }
+        }
+
+        let mut total = 0;
+        for (player, points, reason, pos) in awards {
+            if let Some(hero) = heroes.get_mut(player as usize) {
+                hero.score += points;
+            }
+            total += points;
+            game_log!(
+                Debug,
+                Scoring,
+                "{reason:?} worth {points} to player {player}"
+            );
+            events.push(GameEvent::ScoreAwarded {
+                player,
+                points,
+                reason,
+                pos,
+            });
+        }
+        total
+    }
+}
@@ -0,0 +1,43 @@
+//! Saving a run mid-game and picking it up exactly where it was
+
+use serde::{Deserialize, Serialize};
+
+use crate::{replay::ReplayInput, GameSettings, GameStates};
+
+/// Bumped whenever the serialized [`GameStates`] changes in an incompatible way
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// The whole simulation state of a [`Game`](crate::Game), along with the
+/// inputs received so far so that replays keep working after a restore
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub version: u32,
+    pub settings: GameSettings,
+    pub states: GameStates,
+    pub inputs: Vec<ReplayInput>,
+    /// See [`Replay::checksums`](crate::replay::Replay::checksums)
+    #[serde(default)]
+    pub checksums: Vec<u64>,
+}
+
+impl Snapshot {
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        #[derive(Deserialize)]
+        struct Version {
+            version: u32,
+        }
+        // check the version first, an older layout would fail with a confusing error
+        let Version { version } =
+            serde_json::from_str(json).map_err(|err| format!("invalid snapshot: {err}"))?;
+        if version != SNAPSHOT_VERSION {
+            return Err(format!(
+                "snapshot version {version} is not supported, expected {SNAPSHOT_VERSION}"
+            ));
+        }
+        serde_json::from_str(json).map_err(|err| format!("invalid snapshot: {err}"))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+}
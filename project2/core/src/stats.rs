@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+/// Kill counters, one per enemy type
+#[derive(Default, Clone, Copy, Serialize, Deserialize)]
+pub struct Kills {
+    pub middle_cup: u32,
+    pub big_cup: u32,
+    pub super_big_cup: u32,
+}
+
+impl Kills {
+    pub fn total(&self) -> u32 {
+        self.middle_cup + self.big_cup + self.super_big_cup
+    }
+}
+
+/// Counters accumulated over a single run
+#[derive(Default, Clone, Copy, Serialize, Deserialize)]
+pub struct RunStats {
+    /// Time alive, the run ends with the last hero
+    pub ticks: u32,
+    pub bullets_fired: u32,
+    /// Hero bullets that hit an enemy
+    #[serde(default)]
+    pub bullets_hit: u32,
+    pub hits_taken: u32,
+    pub kills: Kills,
+    /// Longest kill chain of any player, see [`crate::scoring::Chain`]
+    #[serde(default)]
+    pub max_combo: u32,
+    /// Enemy bullets that went close without hitting, see [`crate::event::GameEvent::NearMiss`]
+    #[serde(default)]
+    pub grazes: u32,
+}
+
+impl RunStats {
+    /// Share of the hero bullets that hit, 0 before the first shot
+    pub fn accuracy(&self) -> f32 {
+        if self.bullets_fired == 0 {
+            0.0
+        } else {
+            self.bullets_hit as f32 / self.bullets_fired as f32
+        }
+    }
+}
@@ -0,0 +1,124 @@
+mod common;
+
+use core::{
+    accessibility::{
+        Accessibility, ASSIST_BULLET_SPEED_SCALE, ASSIST_EXTRA_LIVES, MAX_HITBOX_SCALE,
+    },
+    entity::{
+        enemy::{Enemy, EnemyKind, ENEMY_BULLET_SPEED},
+        hero::Hero,
+        CollisionBox,
+    },
+    headless,
+    leaderboard::{Leaderboard, LEADERBOARD_SIZE},
+    replay::{parse_script, Replay},
+    Game, GameSettings,
+};
+
+fn settings(accessibility: Accessibility) -> GameSettings {
+    let mut settings = common::settings(3);
+    settings.accessibility = accessibility;
+    settings
+}
+
+fn assisted() -> Accessibility {
+    Accessibility {
+        assist: true,
+        hitbox_scale: 0.5,
+        ..Accessibility::default()
+    }
+}
+
+#[test]
+fn assist_adds_lives_and_slows_enemy_bullets() {
+    let game = Game::new(settings(Accessibility::default()));
+    assert_eq!(game.player_lives(0), 1);
+    let game = Game::new(settings(assisted()));
+    assert_eq!(game.player_lives(0), 1 + ASSIST_EXTRA_LIVES);
+
+    let speed = |accessibility: Accessibility| {
+        let mut enemy = Enemy::new(EnemyKind::MiddleCup, 100.0, 600.0);
+        let bullets = enemy.fire(None, 10, accessibility.enemy_bullet_speed());
+        bullets[0].motion_state.speed.norm()
+    };
+    assert_eq!(speed(Accessibility::default()), ENEMY_BULLET_SPEED);
+    assert_eq!(
+        speed(assisted()),
+        ENEMY_BULLET_SPEED * ASSIST_BULLET_SPEED_SCALE
+    );
+}
+
+#[test]
+fn the_hitbox_scales_within_bounds() {
+    let hero = Hero::new(0, 100.0, 0.0, 1);
+    let (_, _, width, height) = hero.bounding_box();
+    let (_, _, small_width, small_height) = hero.clone().with_hitbox_scale(0.5).bounding_box();
+    assert_eq!((small_width, small_height), (width * 0.5, height * 0.5));
+
+    let huge = Accessibility {
+        hitbox_scale: 10.0,
+        ..Accessibility::default()
+    };
+    assert_eq!(huge.clamped_hitbox_scale(), MAX_HITBOX_SCALE);
+
+    for hitbox_scale in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+        let broken = Accessibility {
+            hitbox_scale,
+            ..Accessibility::default()
+        };
+        assert_eq!(broken.clamped_hitbox_scale(), 1.0);
+        assert!(!Game::new(settings(broken))
+            .leaderboard_entry("p1")
+            .assisted());
+    }
+}
+
+const SCRIPT: &str = "
+0 space down
+0 d down
+40 d up
+";
+
+#[test]
+fn options_are_recorded_in_replays_and_leaderboard_entries() {
+    let mut game = Game::new(settings(assisted()));
+    headless::run(&mut game, &parse_script(SCRIPT).unwrap(), 400, |_| ());
+
+    let replay = Replay::from_json(&game.export_replay()).unwrap();
+    assert_eq!(replay.accessibility, assisted());
+    assert_eq!(replay.verify(), Ok(game.checksums().len() as u32));
+
+    let entry = game.leaderboard_entry("p1");
+    assert_eq!(entry.accessibility, assisted());
+    assert!(entry.assisted());
+    assert!(!Game::new(settings(Accessibility::default()))
+        .leaderboard_entry("p2")
+        .assisted());
+
+    let leaderboard = Leaderboard::from_json(&game.add_to_leaderboard("[]", "p1")).unwrap();
+    assert_eq!(leaderboard.entries()[0].score, game.score());
+    assert_eq!(leaderboard.entries()[0].accessibility, assisted());
+}
+
+#[test]
+fn the_leaderboard_keeps_the_best_scores() {
+    let game = Game::new(settings(Accessibility::default()));
+    let mut leaderboard = Leaderboard::default();
+    for score in 0..LEADERBOARD_SIZE as u32 {
+        let mut entry = game.leaderboard_entry("p1");
+        entry.score = score * 100;
+        leaderboard.insert(entry);
+    }
+    let mut entry = game.leaderboard_entry("p2");
+    entry.score = 450;
+    assert_eq!(leaderboard.insert(entry.clone()), Some(5));
+    entry.score = 0;
+    assert_eq!(leaderboard.insert(entry), None);
+
+    let scores: Vec<_> = leaderboard
+        .entries()
+        .iter()
+        .map(|entry| entry.score)
+        .collect();
+    assert_eq!(scores, [900, 800, 700, 600, 500, 450, 400, 300, 200, 100]);
+}
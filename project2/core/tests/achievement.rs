@@ -0,0 +1,112 @@
+use std::{cell::RefCell, rc::Rc};
+
+mod common;
+
+use core::{
+    achievement::{AchievementStore, Achievements, Goal, MemoryStore},
+    entity::enemy::EnemyKind,
+    event::GameEvent,
+    headless,
+    replay::parse_script,
+    stats::RunStats,
+    Game,
+};
+
+/// A store the test can look into after handing it to a game
+struct SharedStore(Rc<RefCell<Vec<String>>>);
+
+impl AchievementStore for SharedStore {
+    fn load(&mut self) -> Vec<String> {
+        self.0.borrow().clone()
+    }
+
+    fn save(&mut self, unlocked: &[String]) {
+        *self.0.borrow_mut() = unlocked.to_vec();
+    }
+}
+
+#[test]
+fn goals_are_checked_against_the_stats() {
+    let mut stats = RunStats::default();
+    assert!(!Goal::TotalKills(1).reached(&stats, 0));
+    stats.kills.big_cup = 1;
+    assert!(Goal::TotalKills(1).reached(&stats, 0));
+    assert!(!Goal::Kills(EnemyKind::SuperBigCup, 1).reached(&stats, 0));
+    assert!(Goal::Score(500).reached(&stats, 600));
+
+    stats.ticks = 1200;
+    assert!(Goal::Flawless(1200).reached(&stats, 0));
+    stats.hits_taken = 1;
+    assert!(!Goal::Flawless(1200).reached(&stats, 0));
+    assert!(Goal::Survive(1200).reached(&stats, 0));
+
+    let sharpshooter = Goal::Accuracy {
+        min: 0.5,
+        shots: 10,
+    };
+    stats.bullets_fired = 8;
+    stats.bullets_hit = 8;
+    assert!(!sharpshooter.reached(&stats, 0));
+    stats.bullets_fired = 16;
+    assert_eq!(stats.accuracy(), 0.5);
+    assert!(sharpshooter.reached(&stats, 0));
+}
+
+#[test]
+fn achievements_unlock_once() {
+    let mut achievements = Achievements::new(Box::new(MemoryStore::default()));
+    let mut stats = RunStats::default();
+    stats.kills.middle_cup = 1;
+    let unlocked: Vec<_> = achievements
+        .check(&stats, 100)
+        .iter()
+        .map(|achievement| achievement.id)
+        .collect();
+    assert_eq!(unlocked, ["first_blood"]);
+    assert!(achievements.is_unlocked("first_blood"));
+    assert!(achievements.check(&stats, 100).is_empty());
+}
+
+const SCRIPT: &str = "
+0 space down
+0 d down
+40 d up
+";
+
+fn play(store: &Rc<RefCell<Vec<String>>>) -> (Game, Vec<&'static str>) {
+    let mut game = common::game(1);
+    game.set_achievement_store(Box::new(SharedStore(store.clone())));
+    let mut unlocked = vec![];
+    headless::run(&mut game, &parse_script(SCRIPT).unwrap(), 2000, |game| {
+        unlocked.extend(
+            game.take_events()
+                .into_iter()
+                .filter_map(|event| match event {
+                    GameEvent::AchievementUnlocked { id } => Some(id),
+                    _ => None,
+                }),
+        );
+    });
+    (game, unlocked)
+}
+
+#[test]
+fn unlocked_achievements_persist_across_games() {
+    let store = Rc::new(RefCell::new(vec![]));
+    let (game, unlocked) = play(&store);
+    assert!(game.stats().kills.total() > 0);
+    assert!(unlocked.contains(&"first_blood"));
+    assert!(store.borrow().contains(&"first_blood".to_string()));
+
+    // a later game starts with them, and doesn't announce them again
+    let (game, unlocked) = play(&store);
+    assert!(game.is_unlocked("first_blood"));
+    assert!(!unlocked.contains(&"first_blood"));
+
+    let stats: serde_json::Value = serde_json::from_str(&game.run_stats()).unwrap();
+    assert_eq!(stats["bullets_fired"], game.stats().bullets_fired);
+    assert!(stats["accuracy"].as_f64().unwrap() > 0.0);
+    let achievements: serde_json::Value = serde_json::from_str(&game.achievements()).unwrap();
+    assert_eq!(achievements[0]["id"], "first_blood");
+    assert_eq!(achievements[0]["unlocked"], true);
+}
@@ -0,0 +1,253 @@
+use core::{
+    animation::{library, parse_library, Animator, MAX_PLAYBACK_SPEED},
+    entity::EntityState,
+    render::BitmapAsset::{self, Hero1, Hero2, HeroDown1, HeroDown2},
+};
+
+/// An animator of the `test` set declared by `json`
+fn animator(json: &str) -> Animator {
+    let library = Box::leak(Box::new(parse_library(json).unwrap()));
+    let (name, set) = library.get_key_value("test").unwrap();
+    Animator::from_set(name, set)
+}
+
+/// A set with a single clip in `mode`, over `frames` of `(bitmap, ticks, event)`
+fn clip(mode: &str, frames: &str) -> Animator {
+    animator(&format!(
+        r#"{{ "test": {{
+            "clips": {{ "clip": {{ "mode": "{mode}", "frames": [{frames}] }} }},
+            "states": {{ "normal": "clip" }}
+        }} }}"#
+    ))
+}
+
+/// The sprite after each of `ticks` ticks, with whether the clip finished
+fn play(animator: &mut Animator, ticks: u32) -> Vec<(BitmapAsset, bool)> {
+    (0..ticks)
+        .map(|_| {
+            let finished = animator.tick().finished;
+            (animator.cur_bitmap(), finished)
+        })
+        .collect()
+}
+
+const TWO_FRAMES: &str = r#"
+    { "bitmap": "Hero1", "ticks": 1 },
+    { "bitmap": "Hero2", "ticks": 2 }
+"#;
+
+#[test]
+fn loops_wrap_around_with_per_frame_durations() {
+    let mut animator = clip("loop", TWO_FRAMES);
+    assert_eq!(animator.cur_bitmap(), Hero1);
+    assert_eq!(
+        play(&mut animator, 6),
+        [
+            (Hero2, false),
+            (Hero2, false),
+            (Hero1, true),
+            (Hero2, false),
+            (Hero2, false),
+            (Hero1, true),
+        ]
+    );
+}
+
+#[test]
+fn once_rewinds_and_stops() {
+    let mut animator = clip("once", TWO_FRAMES);
+    assert_eq!(
+        play(&mut animator, 5),
+        [
+            (Hero2, false),
+            (Hero2, false),
+            (Hero1, true),
+            (Hero1, false),
+            (Hero1, false),
+        ]
+    );
+}
+
+#[test]
+fn hold_last_stays_on_the_last_frame() {
+    let mut animator = clip("hold_last", TWO_FRAMES);
+    assert_eq!(
+        play(&mut animator, 5),
+        [
+            (Hero2, false),
+            (Hero2, false),
+            (Hero2, true),
+            (Hero2, false),
+            (Hero2, false),
+        ]
+    );
+}
+
+#[test]
+fn ping_pong_goes_back_and_forth() {
+    let mut animator = clip(
+        "ping_pong",
+        r#"
+        { "bitmap": "Hero1", "ticks": 1 },
+        { "bitmap": "Hero2", "ticks": 1 },
+        { "bitmap": "HeroDown1", "ticks": 1 }
+        "#,
+    );
+    assert_eq!(
+        play(&mut animator, 6),
+        [
+            (Hero2, false),
+            (HeroDown1, false),
+            (Hero2, false),
+            (Hero1, true),
+            (Hero2, false),
+            (HeroDown1, false),
+        ]
+    );
+}
+
+#[test]
+fn frames_fire_their_events_when_entered() {
+    let mut animator = clip(
+        "loop",
+        r#"
+        { "bitmap": "Hero1", "ticks": 1, "event": "start" },
+        { "bitmap": "Hero2", "ticks": 2, "event": "middle" }
+        "#,
+    );
+    let events: Vec<_> = (0..4).map(|_| animator.tick().events).collect();
+    assert_eq!(
+        events,
+        [
+            vec!["start", "middle"],
+            vec![],
+            vec!["start"],
+            vec!["middle"]
+        ]
+    );
+}
+
+#[test]
+fn the_speed_scales_the_playback() {
+    let mut animator = clip("loop", TWO_FRAMES);
+    assert_eq!(animator.speed(), 1.0);
+    // a 3 ticks cycle every 1.5 tick
+    animator.set_speed(2.0);
+    assert_eq!(
+        play(&mut animator, 3),
+        [(Hero2, false), (Hero2, true), (Hero1, true)]
+    );
+
+    let mut animator = clip("loop", TWO_FRAMES);
+    animator.set_speed(0.5);
+    assert_eq!(
+        play(&mut animator, 4),
+        [
+            (Hero1, false),
+            (Hero2, false),
+            (Hero2, false),
+            (Hero2, false)
+        ]
+    );
+
+    animator.set_speed(f32::NAN);
+    assert_eq!(animator.speed(), 1.0);
+    // a huge speed would walk through millions of frames every tick
+    animator.set_speed(1e30);
+    assert_eq!(animator.speed(), MAX_PLAYBACK_SPEED);
+    // a tick into the second frame, plus 16 ticks of the 3 ticks cycle
+    assert_eq!(play(&mut animator, 1), [(Hero1, true)]);
+    animator.set_speed(-1.0);
+    assert_eq!(animator.speed(), 0.0);
+    let frozen = animator.cur_bitmap();
+    assert!(play(&mut animator, 10)
+        .iter()
+        .all(|&(bitmap, _)| bitmap == frozen));
+}
+
+const TRANSITION: &str = r#"{ "test": {
+    "clips": {
+        "fly": { "mode": "loop", "frames": [
+            { "bitmap": "Hero1", "ticks": 1 },
+            { "bitmap": "Hero2", "ticks": 2 }
+        ] },
+        "die": { "mode": "hold_last", "frames": [
+            { "bitmap": "HeroDown1", "ticks": 1 },
+            { "bitmap": "HeroDown2", "ticks": 1 }
+        ] }
+    },
+    "states": { "normal": "fly", "die_animating": "die" },
+    "transitions": [{ "from": "fly", "to": "die", "wait_for_end": WAIT }]
+} }"#;
+
+#[test]
+fn transitions_can_wait_for_the_end_of_the_clip() {
+    let mut immediate = animator(&TRANSITION.replace("WAIT", "false"));
+    immediate.tick();
+    immediate.set_state(EntityState::DieAnimating);
+    assert_eq!(immediate.cur_bitmap(), HeroDown1);
+
+    let mut waiting = animator(&TRANSITION.replace("WAIT", "true"));
+    waiting.tick();
+    waiting.set_state(EntityState::DieAnimating);
+    // the fly cycle ends first
+    assert_eq!(
+        play(&mut waiting, 4),
+        [
+            (Hero2, false),
+            (HeroDown1, false),
+            (HeroDown2, false),
+            (HeroDown2, true),
+        ]
+    );
+}
+
+#[test]
+fn bad_clips_are_rejected() {
+    let set = |clips: &str, states: &str| {
+        format!(r#"{{ "broken": {{ "clips": {{ {clips} }}, "states": {{ {states} }} }} }}"#)
+    };
+    let one_frame = r#""fly": { "mode": "loop", "frames": [{ "bitmap": "Hero1", "ticks": 1 }] }"#;
+    for (json, error) in [
+        (
+            set(
+                r#""fly": { "mode": "loop", "frames": [] }"#,
+                r#""normal": "fly""#,
+            ),
+            "animation set `broken`: clip `fly` has no frame",
+        ),
+        (
+            set(
+                r#""fly": { "mode": "loop", "frames": [{ "bitmap": "Hero1", "ticks": 0 }] }"#,
+                r#""normal": "fly""#,
+            ),
+            "animation set `broken`: clip `fly` has a frame lasting 0 tick",
+        ),
+        (
+            set(one_frame, r#""normal": "walk""#),
+            "animation set `broken`: unknown clip `walk`",
+        ),
+        (
+            set(one_frame, r#""died": "fly""#),
+            "animation set `broken`: no clip for the normal state",
+        ),
+    ] {
+        assert_eq!(parse_library(&json).err().as_deref(), Some(error));
+    }
+
+    let unknown_bitmap = set(
+        r#""fly": { "mode": "loop", "frames": [{ "bitmap": "Hero9", "ticks": 1 }] }"#,
+        r#""normal": "fly""#,
+    );
+    let error = parse_library(&unknown_bitmap).err().unwrap();
+    assert!(error.starts_with("invalid animations:"), "{error}");
+    assert!(error.contains("Hero9"), "{error}");
+}
+
+#[test]
+fn the_bundled_animations_are_valid() {
+    for set in ["hero", "enemy1", "enemy2", "enemy3"] {
+        assert!(library().contains_key(set), "{set}");
+        Animator::new(set);
+    }
+}
@@ -0,0 +1,47 @@
+use std::path::Path;
+
+use core::assets::BitmapAsset;
+
+/// (width, height) from the IHDR chunk, which always comes first in a PNG
+fn png_size(path: &Path) -> (u16, u16) {
+    let bytes = std::fs::read(path).unwrap_or_else(|err| panic!("{}: {err}", path.display()));
+    assert_eq!(&bytes[1..4], b"PNG", "{} is not a PNG", path.display());
+    assert_eq!(&bytes[12..16], b"IHDR");
+    let width = u32::from_be_bytes(bytes[16..20].try_into().unwrap());
+    let height = u32::from_be_bytes(bytes[20..24].try_into().unwrap());
+    (width as u16, height as u16)
+}
+
+#[test]
+fn recorded_sizes_match_the_images() {
+    let images = Path::new(env!("CARGO_MANIFEST_DIR")).join("../frontend/images");
+    for asset in BitmapAsset::ALL {
+        let info = asset.info();
+        assert_eq!(
+            info.size,
+            png_size(&images.join(info.file)),
+            "{:?} is out of date, run `just atlas`",
+            asset
+        );
+    }
+}
+
+#[test]
+fn hitboxes_are_inside_the_sprites() {
+    for asset in BitmapAsset::ALL {
+        let info = asset.info();
+        let (x, y, width, height) = info.hitbox;
+        assert!(x >= 0.0 && y >= 0.0, "{:?}", asset);
+        assert!(x + width <= info.size.0 as f32, "{:?}", asset);
+        assert!(y + height <= info.size.1 as f32, "{:?}", asset);
+    }
+}
+
+#[test]
+fn default_hitbox_is_centered_on_the_position() {
+    let info = BitmapAsset::Enemy1.info();
+    assert_eq!(
+        info.bounding_box((100.0, 200.0)),
+        (100.0, 200.0, 57.0, 43.0)
+    );
+}
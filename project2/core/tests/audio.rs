@@ -0,0 +1,141 @@
+mod common;
+
+use core::{
+    audio::{AudioCue, AudioDirector, MusicState, SoundCue},
+    event::GameEvent,
+    headless,
+    replay::parse_script,
+};
+
+/// Every cue fired while playing `script`, with the tick it fired on
+fn cues_for(seed: u64, script: &str) -> Vec<(u32, AudioCue)> {
+    let mut game = common::game(seed);
+    let inputs = parse_script(script).unwrap();
+
+    let mut tick = 0;
+    let mut cues = vec![];
+    headless::run(&mut game, &inputs, 20000, |game| {
+        tick += 1;
+        cues.extend(game.take_audio_cues().into_iter().map(|cue| (tick, cue)));
+    });
+    cues
+}
+
+#[test]
+fn music_starts_normal_and_ends_with_game_over() {
+    let cues = cues_for(3, "0 space down\n0 d down\n40 d up");
+    let music: Vec<_> = cues
+        .iter()
+        .filter_map(|(_, cue)| match cue {
+            AudioCue::Music { state } => Some(*state),
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(music.first(), Some(&MusicState::Normal));
+    assert_eq!(music.last(), Some(&MusicState::GameOver));
+    assert!(cues.contains(&(
+        cues.last().unwrap().0,
+        AudioCue::Sound {
+            cue: SoundCue::HeroDeath
+        }
+    )));
+}
+
+#[test]
+fn shots_respect_cooldown() {
+    let shot = GameEvent::BulletFired {
+        player: 0,
+        pos: (0.0, 0.0),
+    };
+    let mut director = AudioDirector::new();
+    let mut shot_ticks = vec![];
+    for tick in 0..20 {
+        // two heroes firing on the same tick still make one sound
+        director.tick(&[shot, shot], false, false);
+        let shots = director
+            .take_cues()
+            .into_iter()
+            .filter(|cue| {
+                *cue == AudioCue::Sound {
+                    cue: SoundCue::Shot,
+                }
+            })
+            .count();
+        assert!(shots <= 1);
+        if shots == 1 {
+            shot_ticks.push(tick);
+        }
+    }
+    let cooldown = SoundCue::Shot.cooldown() as usize;
+    assert!(cooldown > 1);
+    assert_eq!(shot_ticks, (0..20).step_by(cooldown).collect::<Vec<_>>());
+}
+
+#[test]
+fn a_scripted_run_plays_the_expected_cues() {
+    use MusicState::*;
+    use SoundCue::*;
+
+    let sound = |cue| AudioCue::Sound { cue };
+    let music = |state| AudioCue::Music { state };
+    let cues = cues_for(5, "0 space down\n0 d down\n20 d up");
+    let (shots, cues): (Vec<_>, Vec<_>) =
+        cues.into_iter().partition(|(_, cue)| *cue == sound(Shot));
+    assert_eq!(shots.len(), 48);
+    assert_eq!(
+        cues,
+        [
+            (1, sound(WaveStart)),
+            (1, music(Normal)),
+            (70, sound(EnemyHit)),
+            (71, sound(SmallExplosion)),
+            (291, sound(WaveStart)),
+            (311, sound(EnemyHit)),
+            (311, music(Boss)),
+            (313, sound(EnemyHit)),
+            (323, sound(EnemyHit)),
+            (332, sound(EnemyHit)),
+            (342, sound(BossPhase)),
+            (342, sound(EnemyHit)),
+            (352, sound(EnemyHit)),
+            (361, sound(EnemyHit)),
+            (371, sound(EnemyHit)),
+            (381, sound(EnemyHit)),
+            (391, sound(EnemyHit)),
+            (392, sound(BossExplosion)),
+            (392, music(Normal)),
+            (401, sound(WaveStart)),
+            (411, music(Boss)),
+            (412, sound(EnemyHit)),
+            (421, sound(EnemyHit)),
+            (429, sound(EnemyHit)),
+            (430, sound(SmallExplosion)),
+            (439, sound(EnemyHit)),
+            (448, sound(EnemyHit)),
+            (457, sound(BossPhase)),
+            (457, sound(EnemyHit)),
+            (466, sound(EnemyHit)),
+            (476, sound(EnemyHit)),
+            (485, sound(EnemyHit)),
+            (494, sound(EnemyHit)),
+            (503, sound(EnemyHit)),
+            (504, sound(BossExplosion)),
+            (512, sound(EnemyHit)),
+            (521, sound(EnemyHit)),
+            (524, sound(HeroHit)),
+            (530, sound(EnemyHit)),
+            (532, sound(HeroDeath)),
+            (532, music(GameOver)),
+        ]
+    );
+}
+
+#[test]
+fn no_shot_without_fire_input() {
+    let cues = cues_for(3, "0 d down");
+    assert!(!cues.iter().any(|(_, cue)| *cue
+        == AudioCue::Sound {
+            cue: SoundCue::Shot
+        }));
+}
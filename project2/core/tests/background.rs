@@ -0,0 +1,75 @@
+mod common;
+
+use core::{
+    background::{render, stage_of_wave},
+    render::{BitmapAsset, Primitive},
+    Game,
+};
+
+/// What the game would draw now
+fn primitives(game: &mut Game) -> Vec<Primitive> {
+    game.prepare_primitives();
+    // SAFETY: the buffer lives in the game and is only changed by the next
+    // `prepare_primitives`, after the copy
+    unsafe { std::slice::from_raw_parts(game.primitives(), game.primitives_len()) }.to_vec()
+}
+
+#[test]
+fn the_background_depends_only_on_the_time() {
+    let (settings, other_seed) = (common::settings(1), common::settings(2));
+    for time in [0.0, 10.5, 1234.25] {
+        let background = render(&settings, 1, time);
+        assert!(!background.is_empty());
+        assert_eq!(background, render(&settings, 1, time));
+        assert_eq!(background, render(&other_seed, 1, time));
+        assert_ne!(background, render(&settings, 1, time + 0.5));
+    }
+}
+
+#[test]
+fn the_tiles_change_with_the_wave() {
+    let settings = common::settings(1);
+    let (width, height) = BitmapAsset::Background.info().size;
+    let first_tile = |wave| render(&settings, wave, 0.0)[0];
+    assert_eq!(
+        first_tile(1),
+        Primitive::new(
+            BitmapAsset::Background,
+            (width as f32 / 2.0, height as f32 / 2.0),
+            0.0
+        )
+    );
+
+    let stages = [1, 5, 9];
+    for (index, &wave) in stages.iter().enumerate() {
+        assert_eq!(stage_of_wave(wave), index);
+        // the same for the whole stage
+        assert_eq!(first_tile(wave), first_tile(wave + 3));
+        for &other in &stages[index + 1..] {
+            assert_ne!(first_tile(wave), first_tile(other));
+        }
+    }
+    assert_eq!(stage_of_wave(0), 0);
+    assert_eq!(first_tile(9), first_tile(1000));
+}
+
+#[test]
+fn the_background_holds_still_while_paused() {
+    let mut game = common::game(1);
+    for _ in 0..30 {
+        game.tick();
+    }
+    // between two ticks
+    assert_eq!(game.advance(25.0), 0);
+    let paused = primitives(&mut game);
+    assert_eq!(primitives(&mut game), paused);
+
+    // the background is drawn first, and moves once time passes again
+    let background = render(&common::settings(1), 1, 30.5);
+    assert_eq!(paused[..background.len()], background[..]);
+    game.advance(10.0);
+    assert_ne!(
+        primitives(&mut game)[..background.len()],
+        paused[..background.len()]
+    );
+}
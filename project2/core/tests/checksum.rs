@@ -0,0 +1,76 @@
+mod common;
+
+use core::{
+    headless,
+    replay::{parse_script, Replay},
+    Game,
+};
+
+const SCRIPT: &str = "
+0 space down
+0 d down
+60 d up
+60 w down
+90 w up
+200 a down
+320 a up
+";
+
+fn play(seed: u64, ticks: u32) -> Game {
+    // survives the whole script
+    let mut settings = common::settings(seed);
+    settings.lives = 5;
+    let mut game = Game::new(settings);
+    headless::run(&mut game, &parse_script(SCRIPT).unwrap(), ticks, |_| ());
+    game
+}
+
+#[test]
+fn same_seed_gives_the_same_hash_stream() {
+    let first = play(21, 1500);
+    let second = play(21, 1500);
+    assert!(!first.checksums().is_empty());
+    assert_eq!(first.checksums(), second.checksums());
+
+    let other_seed = play(22, 1500);
+    assert_ne!(first.checksums(), other_seed.checksums());
+}
+
+#[test]
+fn recorded_replays_verify() {
+    let game = play(21, 800);
+    let replay = Replay::from_json(&game.export_replay()).unwrap();
+    assert_eq!(replay.checksums, game.checksums());
+    assert_eq!(replay.verify(), Ok(game.checksums().len() as u32));
+}
+
+#[test]
+fn the_verifier_reports_the_first_differing_tick() {
+    let game = play(21, 800);
+    let mut replay = Replay::from_json(&game.export_replay()).unwrap();
+
+    // a corrupted checksum
+    let mut corrupted = replay.clone();
+    corrupted.checksums[300] ^= 1;
+    let divergence = corrupted.verify().unwrap_err();
+    assert_eq!(divergence.tick, 300);
+    assert_eq!(divergence.actual, Some(replay.checksums[300]));
+
+    // an input moved later: the hero keeps going right for 5 more ticks
+    let input = replay
+        .inputs
+        .iter_mut()
+        .find(|input| input.tick == 60)
+        .unwrap();
+    input.tick = 65;
+    replay.inputs.sort_by_key(|input| input.tick);
+    assert_eq!(replay.verify().unwrap_err().tick, 60);
+}
+
+#[test]
+fn rewinding_keeps_the_hash_stream_consistent() {
+    let mut game = play(21, 400);
+    let before: Vec<u64> = game.checksums().to_vec();
+    let ticks = game.rewind(50);
+    assert_eq!(game.checksums(), &before[..before.len() - ticks as usize]);
+}
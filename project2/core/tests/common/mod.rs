@@ -0,0 +1,25 @@
+//! Fixtures shared by the integration tests, each of them uses only a part
+#![allow(dead_code)]
+
+use core::{replay::ReplayInput, Game, GameSettings};
+
+/// The play area of the frontend, with a fixed seed
+pub fn settings(seed: u64) -> GameSettings {
+    let mut settings = GameSettings::new(480, 700);
+    settings.seed = seed;
+    settings
+}
+
+pub fn game(seed: u64) -> Game {
+    Game::new(settings(seed))
+}
+
+/// Tick `game` from tick `from` to `to`, feeding the inputs of those ticks
+pub fn advance(game: &mut Game, inputs: &[ReplayInput], from: u32, to: u32) {
+    for tick in from..to {
+        for input in inputs.iter().filter(|input| input.tick == tick) {
+            input.apply(game);
+        }
+        game.tick();
+    }
+}
@@ -0,0 +1,133 @@
+mod common;
+
+use core::{
+    event::GameEvent,
+    input::{Action, AnalogInputEvent, PointerEvent, PointerPhase},
+    scoring::ScoreReason,
+    Game,
+};
+
+fn coop(lives: u8) -> Game {
+    let mut settings = common::settings(13);
+    settings.players = 2;
+    settings.lives = lives;
+    Game::new(settings)
+}
+
+/// Position of the hero of `player`, read from a snapshot
+fn hero_pos(game: &Game, player: usize) -> (f64, f64) {
+    let snapshot: serde_json::Value = serde_json::from_str(&game.save_state()).unwrap();
+    let pos = &snapshot["states"]["heroes"][player]["motion_state"]["pos"];
+    (pos[0].as_f64().unwrap(), pos[1].as_f64().unwrap())
+}
+
+#[test]
+fn each_player_has_their_own_lives() {
+    let mut game = coop(3);
+    let mut hits = [0u8; 2];
+    while hits.iter().all(|&hits| hits == 0) {
+        game.tick();
+        for event in game.take_events() {
+            if let GameEvent::HeroHit { player, .. } = event {
+                hits[player as usize] += 1;
+            }
+        }
+    }
+    for player in 0..2 {
+        assert_eq!(game.player_lives(player), 3 - hits[player as usize]);
+    }
+    assert_eq!(game.player_lives(2), 0);
+    assert_eq!(game.player_score(2), 0);
+}
+
+#[test]
+fn the_game_ends_when_both_heroes_are_out() {
+    let mut game = coop(1);
+    let mut alone = 0;
+    while !game.end() {
+        game.tick();
+        let out = (0..2)
+            .filter(|&player| game.player_lives(player) == 0)
+            .count();
+        if out == 1 {
+            alone += 1;
+            assert!(!game.end());
+        }
+        assert!(game.checksums().len() < 100_000);
+    }
+    assert_eq!(game.player_lives(0), 0);
+    assert_eq!(game.player_lives(1), 0);
+    // one of them kept playing alone for a while
+    assert!(alone > 0);
+}
+
+#[test]
+fn kills_and_score_go_to_the_owner_of_the_bullet() {
+    let mut game = coop(100);
+    game.action(1, Action::Fire, true);
+    let mut kills = 0;
+    for _ in 0..2000 {
+        game.tick();
+        for event in game.take_events() {
+            match event {
+                GameEvent::EnemyKilled { player, .. } => {
+                    assert_eq!(player, Some(1));
+                    kills += 1;
+                }
+                GameEvent::ScoreAwarded {
+                    player,
+                    reason: ScoreReason::Kill,
+                    ..
+                } => assert_eq!(player, 1),
+                GameEvent::BulletFired { player, .. } => assert_eq!(player, 1),
+                _ => (),
+            }
+        }
+    }
+    assert!(kills > 0);
+    assert!(game.player_score(1) > game.player_score(0));
+}
+
+#[test]
+fn sticks_buttons_and_pointers_drive_their_own_player() {
+    let mut game = coop(100);
+    let start = [hero_pos(&game, 0), hero_pos(&game, 1)];
+
+    game.update_analog(1, &AnalogInputEvent::new(1.0, 0.0));
+    for _ in 0..10 {
+        game.tick();
+    }
+    assert_eq!(hero_pos(&game, 0), start[0]);
+    assert!(hero_pos(&game, 1).0 > start[1].0);
+
+    game.update_analog(1, &AnalogInputEvent::new(0.0, 0.0));
+    // button 0 fires
+    game.update_button(0, 0, true);
+    game.tick();
+    let fired: Vec<u8> = game
+        .take_events()
+        .into_iter()
+        .filter_map(|event| match event {
+            GameEvent::BulletFired { player, .. } => Some(player),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(fired, [0]);
+    game.update_button(0, 0, false);
+
+    let before = [hero_pos(&game, 0), hero_pos(&game, 1)];
+    game.update_pointer(0, &PointerEvent::new(PointerPhase::Down, 0.0, 0.0));
+    game.update_pointer(0, &PointerEvent::new(PointerPhase::Move, 0.0, 300.0));
+    for _ in 0..10 {
+        game.tick();
+    }
+    assert!(hero_pos(&game, 0).1 > before[0].1);
+    // the stick was released, only friction moves the second hero
+    assert_eq!(hero_pos(&game, 1).1, before[1].1);
+
+    // unknown players are ignored
+    game.update_analog(5, &AnalogInputEvent::new(1.0, 0.0));
+    game.update_button(5, 0, true);
+    game.update_pointer(5, &PointerEvent::new(PointerPhase::Down, 0.0, 0.0));
+    game.tick();
+}
@@ -0,0 +1,105 @@
+use core::difficulty::{
+    Difficulty, DifficultyPreset, MAX_ENEMIES, MAX_SPEED_FRAC, MIN_FIRE_INTERVAL,
+    MIN_SPAWN_INTERVAL,
+};
+use core::entity::enemy::EnemyKind;
+
+/// Whether `harder` is at least as hard as `easier` on every axis
+fn at_least_as_hard(harder: &Difficulty, easier: &Difficulty) -> bool {
+    harder.level >= easier.level
+        && harder.speed_scale >= easier.speed_scale
+        && harder.fire_interval <= easier.fire_interval
+        && harder.spawn_interval <= easier.spawn_interval
+        && harder.max_enemies >= easier.max_enemies
+        && harder.mix[0] <= easier.mix[0]
+        && harder.mix[2] >= easier.mix[2]
+}
+
+fn samples() -> impl Iterator<Item = (u32, u32)> {
+    let scores = [
+        0,
+        100,
+        600,
+        1000,
+        3000,
+        10_000,
+        100_000,
+        10_000_000,
+        u32::MAX,
+    ];
+    let ticks = [0, 1, 100, 1200, 12000, 100_000, u32::MAX];
+    scores
+        .into_iter()
+        .flat_map(move |score| ticks.into_iter().map(move |tick| (score, tick)))
+}
+
+#[test]
+fn the_curve_stays_within_bounds() {
+    for preset in DifficultyPreset::ALL {
+        for (score, ticks) in samples() {
+            let difficulty = Difficulty::at(preset, score, ticks);
+            assert!((0.0..=1.0).contains(&difficulty.level));
+            assert!(difficulty.speed_scale > 0.0 && difficulty.speed_scale <= MAX_SPEED_FRAC);
+            assert!(difficulty.fire_interval >= MIN_FIRE_INTERVAL);
+            assert!(difficulty.spawn_interval >= MIN_SPAWN_INTERVAL);
+            assert!((1..=MAX_ENEMIES).contains(&difficulty.max_enemies));
+            assert!(difficulty.mix.iter().all(|share| *share >= 0.0));
+            assert!((difficulty.mix.iter().sum::<f32>() - 1.0).abs() < 1e-5);
+        }
+    }
+}
+
+#[test]
+fn the_curve_only_gets_harder() {
+    for preset in DifficultyPreset::ALL {
+        let mut previous = Difficulty::at(preset, 0, 0);
+        for score in (0..50_000).step_by(100) {
+            let difficulty = Difficulty::at(preset, score, 0);
+            assert!(
+                at_least_as_hard(&difficulty, &previous),
+                "{preset:?} at {score}"
+            );
+            previous = difficulty;
+        }
+        let mut previous = Difficulty::at(preset, 0, 0);
+        for ticks in (0..200_000).step_by(50) {
+            let difficulty = Difficulty::at(preset, 0, ticks);
+            assert!(
+                at_least_as_hard(&difficulty, &previous),
+                "{preset:?} at {ticks}"
+            );
+            previous = difficulty;
+        }
+        assert!(at_least_as_hard(
+            &Difficulty::at(preset, 10_000, 6000),
+            &Difficulty::at(preset, 10_000, 0)
+        ));
+    }
+}
+
+#[test]
+fn presets_are_ordered() {
+    for (score, ticks) in samples() {
+        for pair in DifficultyPreset::ALL.windows(2) {
+            let easier = Difficulty::at(pair[0], score, ticks);
+            let harder = Difficulty::at(pair[1], score, ticks);
+            assert!(
+                at_least_as_hard(&harder, &easier),
+                "{pair:?} at {score}, {ticks}"
+            );
+        }
+    }
+}
+
+#[test]
+fn the_mix_starts_with_middle_cups_only() {
+    let start = Difficulty::at(DifficultyPreset::Normal, 0, 0);
+    assert_eq!(start.max_enemies, 1);
+    assert_eq!(start.pick(0.999), EnemyKind::MiddleCup);
+
+    let late = Difficulty::at(DifficultyPreset::Normal, 30_000, 0);
+    assert_eq!(late.pick(0.0), EnemyKind::MiddleCup);
+    assert_eq!(late.pick(0.6), EnemyKind::BigCup);
+    assert_eq!(late.pick(0.999), EnemyKind::SuperBigCup);
+    assert!(late.speed_scale > start.speed_scale);
+}
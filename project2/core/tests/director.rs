@@ -0,0 +1,122 @@
+mod common;
+
+use core::{
+    difficulty::{
+        Difficulty, DifficultyPreset, MAX_ENEMIES, MIN_FIRE_INTERVAL, MIN_SPAWN_INTERVAL,
+    },
+    director::{Director, Performance, EVALUATION_INTERVAL, MAX_INTENSITY},
+    entity::enemy::EnemyKind,
+    event::GameEvent,
+    headless,
+    replay::{parse_script, Replay},
+    Game,
+};
+
+const KILL: GameEvent = GameEvent::EnemyKilled {
+    kind: EnemyKind::MiddleCup,
+    pos: (0.0, 0.0),
+    score: 1,
+    player: Some(0),
+};
+const HIT: GameEvent = GameEvent::HeroHit {
+    player: 0,
+    pos: (0.0, 0.0),
+};
+
+/// Feed `events` on the first tick of each window, for `windows` windows,
+/// returns the decisions
+fn feed(director: &mut Director, events: &[GameEvent], windows: u32) -> Vec<GameEvent> {
+    let mut decisions = vec![];
+    for tick in 0..windows * EVALUATION_INTERVAL {
+        let events = if tick % EVALUATION_INTERVAL == 0 {
+            events
+        } else {
+            &[]
+        };
+        decisions.extend(director.observe(events));
+    }
+    decisions
+}
+
+#[test]
+fn kills_raise_and_hits_lower_the_intensity() {
+    let mut director = Director::default();
+    let decisions = feed(&mut director, &[KILL; 4], 1);
+    assert!(director.intensity() > 0.0);
+    assert_eq!(
+        decisions,
+        vec![GameEvent::DirectorAdjusted {
+            intensity: director.intensity(),
+            performance: Performance {
+                kills: 4,
+                hits_taken: 0,
+                near_misses: 0,
+            },
+        }]
+    );
+
+    let raised = director.intensity();
+    feed(&mut director, &[HIT], 1);
+    assert!(director.intensity() < raised);
+}
+
+#[test]
+fn the_intensity_stays_within_limits() {
+    let mut director = Director::default();
+    feed(&mut director, &[KILL; 50], 20);
+    assert_eq!(director.intensity(), MAX_INTENSITY);
+    // at the limit, nothing more to record
+    assert!(feed(&mut director, &[KILL; 50], 1).is_empty());
+
+    let curve = Difficulty::at(DifficultyPreset::Lunatic, 100_000, 100_000);
+    let adjusted = director.adjust(curve);
+    assert!(adjusted.fire_interval >= MIN_FIRE_INTERVAL);
+    assert!(adjusted.spawn_interval >= MIN_SPAWN_INTERVAL);
+    assert!(adjusted.max_enemies <= MAX_ENEMIES);
+    assert_eq!(adjusted.speed_scale, curve.speed_scale);
+
+    feed(&mut director, &[HIT; 3], 20);
+    assert_eq!(director.intensity(), -MAX_INTENSITY);
+    let curve = Difficulty::at(DifficultyPreset::Easy, 0, 0);
+    let adjusted = director.adjust(curve);
+    assert!(adjusted.max_enemies >= 1);
+    assert!(adjusted.fire_interval > curve.fire_interval);
+    assert!(adjusted.spawn_interval > curve.spawn_interval);
+}
+
+const SCRIPT: &str = "
+0 space down
+0 d down
+40 d up
+300 a down
+330 a up
+";
+
+fn play(ticks: u32) -> (Game, Vec<GameEvent>) {
+    let mut settings = common::settings(7);
+    settings.lives = 3;
+    settings.adaptive = true;
+    let mut game = Game::new(settings);
+    let mut decisions = vec![];
+    headless::run(&mut game, &parse_script(SCRIPT).unwrap(), ticks, |game| {
+        decisions.extend(
+            game.take_events()
+                .into_iter()
+                .filter(|event| matches!(event, GameEvent::DirectorAdjusted { .. })),
+        );
+    });
+    (game, decisions)
+}
+
+#[test]
+fn adaptive_runs_are_deterministic_and_replayable() {
+    let (first, decisions) = play(2000);
+    let (second, again) = play(2000);
+    assert!(!decisions.is_empty());
+    assert_eq!(decisions, again);
+    assert_eq!(first.checksums(), second.checksums());
+
+    let replay = Replay::from_json(&first.export_replay()).unwrap();
+    assert!(replay.adaptive);
+    assert_eq!(replay.verify(), Ok(first.checksums().len() as u32));
+}
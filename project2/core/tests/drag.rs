@@ -0,0 +1,81 @@
+mod common;
+
+use core::{
+    entity::hero::{Hero, MAX_SPEED},
+    input::{
+        Action, ActionEvent, ActionEventReciever, PointerEvent, PointerEventReciever, PointerPhase,
+    },
+    GameSettings,
+};
+
+fn pointer(hero: &mut Hero, phase: PointerPhase, x: f32, y: f32) {
+    hero.update_pointer(&PointerEvent::new(phase, x, y));
+}
+
+/// Drag the hero by `(dx, dy)` from wherever the pointer went down, returns
+/// the speeds over the following ticks
+fn drag(hero: &mut Hero, settings: &GameSettings, (dx, dy): (f32, f32), ticks: u32) -> Vec<f32> {
+    pointer(hero, PointerPhase::Down, 10.0, 10.0);
+    pointer(hero, PointerPhase::Move, 10.0 + dx, 10.0 + dy);
+    (0..ticks)
+        .map(|_| {
+            hero.tick(settings);
+            hero.motion_state.speed.norm()
+        })
+        .collect()
+}
+
+#[test]
+fn the_hero_follows_the_drag_up_to_its_top_speed() {
+    let settings = common::settings(0);
+    for focused in [false, true] {
+        let mut hero = Hero::new(0, 100.0, 100.0, 1);
+        hero.update(&ActionEvent {
+            action: Action::Focus,
+            pressed: focused,
+        });
+        let top_speed = MAX_SPEED * hero.speed_scale();
+        let speeds = drag(&mut hero, &settings, (150.0, 80.0), 200);
+
+        assert!(speeds.iter().all(|&speed| speed <= top_speed + 1e-4));
+        assert!((speeds[0] - top_speed).abs() < 1e-4);
+        // keeps the offset between the hero and the pointer
+        let pos = hero.motion_state.pos;
+        assert!((pos.x - 250.0).abs() < 1e-3 && (pos.y - 180.0).abs() < 1e-3);
+        assert_eq!(*speeds.last().unwrap(), 0.0);
+        // and fires meanwhile
+        assert!(hero.firing());
+    }
+}
+
+#[test]
+fn the_hero_stops_when_the_drag_ends() {
+    let settings = common::settings(0);
+    let mut hero = Hero::new(0, 100.0, 100.0, 1);
+    let speeds = drag(&mut hero, &settings, (300.0, 0.0), 10);
+    assert!(speeds.iter().all(|&speed| speed > 0.0));
+
+    pointer(&mut hero, PointerPhase::Up, 0.0, 0.0);
+    assert!(!hero.firing());
+    let released = hero.motion_state.pos;
+    for _ in 0..20 {
+        hero.tick(&settings);
+    }
+    let rest = hero.motion_state.pos;
+    assert_eq!(rest, released);
+    assert_eq!(hero.motion_state.speed.norm(), 0.0);
+
+    // moving the pointer without a drag does nothing
+    pointer(&mut hero, PointerPhase::Move, 400.0, 400.0);
+    hero.tick(&settings);
+    assert_eq!(hero.motion_state.pos, rest);
+}
+
+#[test]
+fn dragging_stays_within_the_play_area() {
+    let settings = common::settings(0);
+    let mut hero = Hero::new(0, 100.0, 100.0, 1);
+    drag(&mut hero, &settings, (-1000.0, 5000.0), 400);
+    let pos = hero.motion_state.pos;
+    assert_eq!((pos.x, pos.y), (0.0, 700.0));
+}
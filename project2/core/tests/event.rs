@@ -0,0 +1,67 @@
+mod common;
+
+use core::{
+    event::GameEvent,
+    headless,
+    replay::{parse_script, ReplayInput},
+    Game, GameSettings,
+};
+
+/// Long enough to meet a boss, short enough to end the run
+fn settings() -> GameSettings {
+    let mut settings = common::settings(5);
+    settings.lives = 100;
+    settings
+}
+
+fn script() -> Vec<ReplayInput> {
+    parse_script("0 space down").unwrap()
+}
+
+#[test]
+fn a_scripted_run_reports_what_happened() {
+    let mut game = Game::new(settings());
+    let mut events = Vec::new();
+    headless::run(&mut game, &script(), 20_000, |game| {
+        events.extend(game.take_events());
+        // draining empties the queue
+        assert!(game.take_events().is_empty());
+    });
+    assert!(game.end());
+    let summary = headless::simulate(settings(), &script(), 20_000);
+
+    let count = |matches: fn(&GameEvent) -> bool| events.iter().filter(|e| matches(e)).count();
+    assert_eq!(
+        count(|e| matches!(e, GameEvent::BulletFired { player: 0, .. })),
+        summary.bullets_fired as usize
+    );
+    assert!(count(|e| matches!(e, GameEvent::EnemyHit { .. })) > 0);
+    assert_eq!(
+        count(|e| matches!(
+            e,
+            GameEvent::EnemyKilled {
+                player: Some(0),
+                ..
+            }
+        )),
+        summary.kills.total() as usize
+    );
+    assert_eq!(
+        count(|e| matches!(e, GameEvent::HeroHit { player: 0, .. })),
+        summary.hits_taken as usize
+    );
+    assert_eq!(summary.hits_taken, 100);
+    assert!(count(|e| matches!(e, GameEvent::WaveStarted { .. })) > 0);
+    assert!(count(|e| matches!(e, GameEvent::BossPhaseChanged { phase: 1 })) > 0);
+    assert_eq!(count(|e| matches!(e, GameEvent::PickupCollected { .. })), 0);
+
+    // the hero dies once, and that ends the run
+    assert_eq!(count(|e| matches!(e, GameEvent::HeroDied { .. })), 1);
+    let last_death = events
+        .iter()
+        .rposition(|e| matches!(e, GameEvent::HeroDied { player: 0, .. }));
+    let last_hit = events
+        .iter()
+        .rposition(|e| matches!(e, GameEvent::HeroHit { .. }));
+    assert!(last_hit < last_death);
+}
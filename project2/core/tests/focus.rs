@@ -0,0 +1,87 @@
+mod common;
+
+use core::{
+    entity::{
+        hero::{Hero, FOCUS_SPEED_SCALE, FOCUS_SPREAD_SCALE, MAX_SPEED},
+        weapon::Weapon,
+    },
+    input::{Action, ActionEvent, ActionEventReciever},
+};
+
+fn press(hero: &mut Hero, action: Action, pressed: bool) {
+    hero.update(&ActionEvent { action, pressed });
+}
+
+/// Top speed reached holding right for a while
+fn top_speed(focused: bool) -> f32 {
+    // wide enough to never reach the edge
+    let mut settings = common::settings(0);
+    settings.width = 10_000;
+    let mut hero = Hero::new(0, 0.0, 0.0, 1);
+    press(&mut hero, Action::MoveRight, true);
+    press(&mut hero, Action::Focus, focused);
+    let mut top: f32 = 0.0;
+    for _ in 0..100 {
+        hero.tick(&settings);
+        top = top.max(hero.motion_state.speed.norm());
+    }
+    top
+}
+
+#[test]
+fn focus_slows_the_hero_down() {
+    let mut hero = Hero::new(0, 0.0, 0.0, 1);
+    assert_eq!(hero.speed_scale(), 1.0);
+    press(&mut hero, Action::Focus, true);
+    assert!(hero.focused());
+    assert_eq!(hero.speed_scale(), FOCUS_SPEED_SCALE);
+    press(&mut hero, Action::Focus, false);
+    assert_eq!(hero.speed_scale(), 1.0);
+
+    assert!((top_speed(false) - MAX_SPEED).abs() < 1e-3);
+    assert!((top_speed(true) - MAX_SPEED * FOCUS_SPEED_SCALE).abs() < 1e-3);
+}
+
+/// Horizontal speeds of the bullets of one shot
+fn fan(hero: &Hero) -> Vec<f32> {
+    hero.shoot()
+        .map(|bullet| bullet.motion_state.speed.x)
+        .collect()
+}
+
+#[test]
+fn focus_narrows_the_spread() {
+    // a single stream goes straight up either way
+    let mut hero = Hero::new(0, 100.0, 0.0, 1);
+    assert_eq!(fan(&hero), [0.0]);
+    press(&mut hero, Action::Focus, true);
+    assert_eq!(fan(&hero), [0.0]);
+
+    let mut hero = Hero::new(0, 100.0, 0.0, 1);
+    hero.weapon = Weapon {
+        streams: 3,
+        ..Weapon::default()
+    };
+    let wide = fan(&hero);
+    assert_eq!(wide.len(), 3);
+    press(&mut hero, Action::Focus, true);
+    let narrow = fan(&hero);
+    assert_eq!(narrow.len(), wide.len());
+
+    let width = |fan: &[f32]| {
+        fan.iter().cloned().fold(f32::MIN, f32::max) - fan.iter().cloned().fold(f32::MAX, f32::min)
+    };
+    assert!(width(&wide) > 0.0);
+    assert!((width(&narrow) - width(&wide) * FOCUS_SPREAD_SCALE).abs() < 0.05);
+}
+
+#[test]
+fn the_hitbox_is_shown_only_while_focused() {
+    let mut hero = Hero::new(0, 100.0, 50.0, 1);
+    assert!(hero.hitbox_indicator(0).is_none());
+    press(&mut hero, Action::Focus, true);
+    assert!(hero.hitbox_indicator(0).is_some());
+    // not while dying
+    hero.hit();
+    assert!(hero.hitbox_indicator(0).is_none());
+}
@@ -0,0 +1,258 @@
+mod common;
+
+use core::{
+    input::{
+        Action, ActionEvent, AnalogInputEvent, InputMap, StickSettings, UserInput, MAX_DEADZONE,
+        MAX_RESPONSE_EXPONENT, MIN_RESPONSE_EXPONENT,
+    },
+    replay::Replay,
+};
+
+fn press(input: &mut UserInput, action: Action) {
+    input.update(&ActionEvent {
+        action,
+        pressed: true,
+    });
+}
+
+fn release(input: &mut UserInput, action: Action) {
+    input.update(&ActionEvent {
+        action,
+        pressed: false,
+    });
+}
+
+fn direction(input: &UserInput) -> (f32, f32) {
+    let direction = input.direction();
+    (direction.x, direction.y)
+}
+
+/// Every ordering of pressing and releasing `a` and `b`, each press before its release
+fn orderings(a: Action, b: Action) -> Vec<Vec<(Action, bool)>> {
+    let events = [(a, true), (a, false), (b, true), (b, false)];
+    let mut orderings = vec![];
+    for i in 0..4 {
+        for j in 0..4 {
+            for k in 0..4 {
+                for l in 0..4 {
+                    let order = [i, j, k, l];
+                    let mut seen = [false; 4];
+                    if order.iter().any(|&n| std::mem::replace(&mut seen[n], true)) {
+                        continue;
+                    }
+                    let position = |n| order.iter().position(|&m| m == n).unwrap();
+                    if position(0) < position(1) && position(2) < position(3) {
+                        orderings.push(order.iter().map(|&n| events[n]).collect());
+                    }
+                }
+            }
+        }
+    }
+    orderings
+}
+
+/// The most recently pressed action that is still held
+fn expected_winner(history: &[(Action, bool)]) -> Option<Action> {
+    let held = |action| {
+        history
+            .iter()
+            .rev()
+            .find(|(a, _)| *a == action)
+            .is_some_and(|(_, pressed)| *pressed)
+    };
+    history
+        .iter()
+        .rev()
+        .find(|(action, pressed)| *pressed && held(*action))
+        .map(|(action, _)| *action)
+}
+
+fn check_axis(negative: Action, positive: Action, axis: impl Fn((f32, f32)) -> f32) {
+    let orderings = orderings(negative, positive);
+    assert_eq!(orderings.len(), 6);
+    for ordering in orderings {
+        let mut input = UserInput::default();
+        for step in 0..ordering.len() {
+            let (action, pressed) = ordering[step];
+            if pressed {
+                press(&mut input, action);
+            } else {
+                release(&mut input, action);
+            }
+            let expected = match expected_winner(&ordering[..=step]) {
+                Some(action) if action == negative => -1.0,
+                Some(_) => 1.0,
+                None => 0.0,
+            };
+            assert_eq!(
+                axis(direction(&input)),
+                expected,
+                "after {:?}",
+                &ordering[..=step]
+            );
+        }
+    }
+}
+
+#[test]
+fn horizontal_orderings() {
+    check_axis(Action::MoveLeft, Action::MoveRight, |(x, _)| x);
+}
+
+#[test]
+fn vertical_orderings() {
+    check_axis(Action::MoveDown, Action::MoveUp, |(_, y)| y);
+}
+
+#[test]
+fn releasing_the_last_pressed_direction_restores_the_held_one() {
+    let mut input = UserInput::default();
+    press(&mut input, Action::MoveLeft);
+    press(&mut input, Action::MoveRight);
+    assert_eq!(direction(&input), (1.0, 0.0));
+    release(&mut input, Action::MoveRight);
+    assert_eq!(direction(&input), (-1.0, 0.0));
+}
+
+#[test]
+fn axes_are_independent() {
+    let mut input = UserInput::default();
+    press(&mut input, Action::MoveUp);
+    press(&mut input, Action::MoveLeft);
+    press(&mut input, Action::MoveDown);
+    release(&mut input, Action::MoveLeft);
+    assert_eq!(direction(&input), (0.0, -1.0));
+    release(&mut input, Action::MoveDown);
+    assert_eq!(direction(&input), (0.0, 1.0));
+}
+
+#[test]
+fn diagonals_are_normalized() {
+    let mut input = UserInput::default();
+    press(&mut input, Action::MoveUp);
+    press(&mut input, Action::MoveRight);
+    let (x, y) = direction(&input);
+    assert!((x.hypot(y) - 1.0).abs() < 1e-6);
+    assert_eq!(x, y);
+}
+
+#[test]
+fn input_map_binds_several_keys_per_action() {
+    let mut map = InputMap::default();
+    map.bind(Action::Fire, "z");
+    for key in [" ", "z", "Z"] {
+        assert_eq!(map.actions(key).collect::<Vec<_>>(), [Action::Fire]);
+    }
+    map.unbind(Action::Fire, " ");
+    assert_eq!(map.actions(" ").count(), 0);
+
+    let json = map.to_json();
+    assert_eq!(InputMap::from_json(&json).unwrap(), map);
+}
+
+#[test]
+fn stick_deadzone_and_response_curve() {
+    let settings = StickSettings::new(0.2, 2.0);
+    let apply = |x, y| {
+        let stick = settings.apply(&AnalogInputEvent::new(x, y));
+        (stick.x, stick.y)
+    };
+    assert_eq!(apply(0.1, -0.15), (0.0, 0.0));
+    assert_eq!(apply(0.0, 1.0), (0.0, 1.0));
+    // halfway out of the deadzone, squared
+    let (x, _) = apply(-0.6, 0.0);
+    assert!((x + 0.25).abs() < 1e-6);
+    // overshooting corners are clamped to full deflection
+    let (x, y) = apply(1.0, 1.0);
+    assert!((x.hypot(y) - 1.0).abs() < 1e-6);
+}
+
+#[test]
+fn stick_settings_are_clamped() {
+    let settings = StickSettings::new(1.0, 0.0);
+    assert_eq!(settings.deadzone, MAX_DEADZONE);
+    assert_eq!(settings.response_exponent, MIN_RESPONSE_EXPONENT);
+    let settings = StickSettings::new(-0.5, f32::INFINITY);
+    assert_eq!(settings.deadzone, 0.0);
+    assert_eq!(settings.response_exponent, MAX_RESPONSE_EXPONENT);
+    let settings = StickSettings::new(f32::NAN, f32::NAN);
+    assert_eq!((settings.deadzone, settings.response_exponent), (0.0, 1.0));
+    assert_eq!(settings.apply(&AnalogInputEvent::new(0.5, 0.0)).x, 0.5);
+
+    // fields set directly are clamped when applied, full deflection stays full
+    let settings = StickSettings {
+        deadzone: 1.0,
+        response_exponent: -1.0,
+    };
+    for (x, y) in [(1.0, 0.0), (0.0, -1.0), (0.9, 0.9)] {
+        let stick = settings.apply(&AnalogInputEvent::new(x, y));
+        assert!((stick.x.hypot(stick.y) - 1.0).abs() < 1e-6, "{x} {y}");
+    }
+    assert_eq!(
+        settings.apply(&AnalogInputEvent::new(0.9, 0.0)),
+        AnalogInputEvent::new(0.0, 0.0)
+    );
+    let stick = settings.apply(&AnalogInputEvent::new(f32::NAN, 0.0));
+    assert_eq!((stick.x, stick.y), (0.0, 0.0));
+}
+
+#[test]
+fn held_directions_override_the_stick() {
+    let mut input = UserInput::default();
+    input.stick.x = 0.5;
+    assert_eq!(direction(&input), (0.5, 0.0));
+    press(&mut input, Action::MoveUp);
+    assert_eq!(direction(&input), (0.0, 1.0));
+}
+
+#[test]
+fn stick_positions_are_sanitized() {
+    let stick = AnalogInputEvent::new(f32::NAN, -3.0).sanitized();
+    assert_eq!((stick.x, stick.y), (0.0, -1.0));
+    let stick = AnalogInputEvent::new(f32::INFINITY, 0.25).sanitized();
+    assert_eq!((stick.x, stick.y), (0.0, 0.25));
+
+    // before the deadzone: a huge axis is a full deflection, not a spike
+    let settings = StickSettings::new(0.2, 2.0);
+    let stick = settings.apply(&AnalogInputEvent::new(f32::NEG_INFINITY, 50.0));
+    assert_eq!((stick.x, stick.y), (0.0, 1.0));
+
+    // and when fed straight to the game, which records what it used
+    let mut broken = common::game(1);
+    let mut clean = common::game(1);
+    for tick in 0..40 {
+        if tick == 10 {
+            broken.analog(0, &AnalogInputEvent::new(f32::NAN, 40.0));
+            clean.analog(0, &AnalogInputEvent::new(0.0, 1.0));
+        }
+        broken.tick();
+        clean.tick();
+    }
+    assert_eq!(broken.checksums(), clean.checksums());
+    let replay = Replay::from_json(&broken.export_replay()).unwrap();
+    assert_eq!(replay.verify(), Ok(40));
+}
+
+#[test]
+fn bindings_of_unknown_players_are_rejected() {
+    let mut game = common::game(1);
+    game.bind_key(0, Action::Fire, "z").unwrap();
+    assert!(game.key_triggers("z", Action::Fire));
+    game.unbind_key(0, Action::Fire, "z").unwrap();
+    assert!(!game.key_triggers("z", Action::Fire));
+
+    assert_eq!(game.input_map(1), Err("no player 1".into()));
+    assert_eq!(
+        game.bind_key(1, Action::Fire, "z"),
+        Err("no player 1".into())
+    );
+    assert_eq!(
+        game.unbind_key(1, Action::Fire, "z"),
+        Err("no player 1".into())
+    );
+    assert_eq!(
+        game.set_input_map(1, &game.input_map(0).unwrap()),
+        Err("no player 1".into())
+    );
+    assert!(!game.key_triggers("z", Action::Fire));
+}
@@ -0,0 +1,112 @@
+mod common;
+
+use std::{cell::RefCell, rc::Rc};
+
+use core::{
+    game_log,
+    input::Action,
+    log::{self, LogCategory, LogLevel, LogSink},
+    Game,
+};
+
+type Lines = Rc<RefCell<Vec<(LogLevel, LogCategory, String)>>>;
+
+/// Keeps what it is given, for the test to look at
+struct CaptureSink(Lines);
+
+impl LogSink for CaptureSink {
+    fn write(&mut self, level: LogLevel, category: LogCategory, message: &str) {
+        self.0
+            .borrow_mut()
+            .push((level, category, message.to_string()));
+    }
+}
+
+/// The logger is per thread, and every test runs on its own
+fn capture() -> Lines {
+    let lines = Lines::default();
+    log::set_sink(Box::new(CaptureSink(lines.clone())));
+    lines
+}
+
+#[test]
+fn levels_parse_from_their_names() {
+    for (name, level) in [
+        ("off", LogLevel::Off),
+        ("error", LogLevel::Error),
+        ("warn", LogLevel::Warn),
+        ("info", LogLevel::Info),
+        ("debug", LogLevel::Debug),
+        ("trace", LogLevel::Trace),
+    ] {
+        assert_eq!(name.parse(), Ok(level));
+    }
+    assert_eq!(
+        "loud".parse::<LogLevel>(),
+        Err("unknown log level `loud`".to_string())
+    );
+    assert!("Warn".parse::<LogLevel>().is_err());
+}
+
+#[test]
+fn messages_less_severe_than_the_level_are_dropped() {
+    let lines = capture();
+    // warnings and worse by default
+    assert!(log::enabled(LogLevel::Warn, LogCategory::Spawn));
+    assert!(!log::enabled(LogLevel::Info, LogCategory::Spawn));
+
+    log::set_level(LogLevel::Debug);
+    game_log!(Error, Spawn, "error {}", 1);
+    game_log!(Debug, Spawn, "debug {}", 2);
+    game_log!(Trace, Spawn, "trace {}", 3);
+    assert_eq!(
+        *lines.borrow(),
+        [
+            (LogLevel::Error, LogCategory::Spawn, "error 1".to_string()),
+            (LogLevel::Debug, LogCategory::Spawn, "debug 2".to_string()),
+        ]
+    );
+
+    Game::set_log_level(LogLevel::Off);
+    game_log!(Error, Spawn, "nothing gets through");
+    assert_eq!(lines.borrow().len(), 2);
+    // and off is not a level messages can be logged at
+    Game::set_log_level(LogLevel::Trace);
+    assert!(!log::enabled(LogLevel::Off, LogCategory::Spawn));
+}
+
+#[test]
+fn categories_are_enabled_one_by_one() {
+    let lines = capture();
+    log::set_level(LogLevel::Trace);
+    Game::set_log_category(LogCategory::Net, false);
+    Game::set_log_category(LogCategory::Input, false);
+    assert!(!log::enabled(LogLevel::Error, LogCategory::Net));
+    assert!(!log::enabled(LogLevel::Error, LogCategory::Input));
+    assert!(log::enabled(LogLevel::Trace, LogCategory::Collision));
+
+    game_log!(Info, Net, "dropped");
+    game_log!(Info, Scoring, "kept");
+    Game::set_log_category(LogCategory::Net, true);
+    game_log!(Info, Net, "kept too");
+    let messages: Vec<_> = lines
+        .borrow()
+        .iter()
+        .map(|(_, _, message)| message.clone())
+        .collect();
+    assert_eq!(messages, ["kept", "kept too"]);
+    assert!(!log::enabled(LogLevel::Error, LogCategory::Input));
+}
+
+#[test]
+fn the_game_logs_through_the_sink() {
+    let lines = capture();
+    Game::set_log_level(LogLevel::Trace);
+    let mut game = common::game(0);
+    game.action(0, Action::Fire, true);
+    assert!(lines.borrow().contains(&(
+        LogLevel::Trace,
+        LogCategory::Input,
+        "player 0: Fire down".to_string()
+    )));
+}
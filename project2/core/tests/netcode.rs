@@ -0,0 +1,143 @@
+mod common;
+
+use core::{
+    input::Action,
+    netcode::{InputFrame, LinkConditions, LoopbackTransport, RollbackSession, MAX_ROLLBACK},
+    GameSettings,
+};
+
+type Session = RollbackSession<LoopbackTransport>;
+
+/// What each player holds at `tick`, changing often to defeat the predictions,
+/// and steady after tick 250 so that both peers can catch up
+fn scripted_frame(player: u8, tick: u32) -> InputFrame {
+    let frame = InputFrame::default().with(Action::Fire, true);
+    if tick >= 250 {
+        return frame;
+    }
+    match player {
+        0 => frame
+            .with(Action::MoveRight, tick % 40 < 20)
+            .with(Action::MoveUp, tick % 70 < 10),
+        _ => frame
+            .with(Action::MoveLeft, tick % 30 < 15)
+            .with(Action::Focus, tick % 50 < 25),
+    }
+}
+
+/// Enough lives for both heroes to play the whole script
+fn settings(seed: u64) -> GameSettings {
+    let mut settings = common::settings(seed);
+    settings.lives = 100;
+    settings
+}
+
+fn connect(conditions: LinkConditions, seeds: (u64, u64), input_delay: u32) -> [Session; 2] {
+    let (a, b) = LoopbackTransport::pair(conditions, 5);
+    [
+        RollbackSession::new(settings(seeds.0), 0, input_delay, a),
+        RollbackSession::new(settings(seeds.1), 1, input_delay, b),
+    ]
+}
+
+/// Both heroes still have lives left on both peers, so that the agreement is
+/// not about two empty play areas
+fn assert_heroes_alive(peers: &[Session; 2]) {
+    for peer in peers {
+        let heroes = peer.states().heroes();
+        assert_eq!(heroes.len(), 2);
+        assert!(heroes.iter().all(|hero| hero.lives > 0 && !hero.out()));
+    }
+}
+
+/// Run both peers in lockstep for `ticks` wall clock ticks
+fn run(peers: &mut [Session; 2], ticks: u32) {
+    for _ in 0..ticks {
+        for (player, peer) in peers.iter_mut().enumerate() {
+            peer.add_local_input(scripted_frame(player as u8, peer.current_tick()));
+            peer.tick();
+        }
+        peers[0].transport().advance();
+    }
+}
+
+#[test]
+fn peers_agree_over_a_perfect_link() {
+    let mut peers = connect(LinkConditions::PERFECT, (9, 9), 2);
+    run(&mut peers, 400);
+    let [a, b] = &peers;
+    assert_eq!(a.current_tick(), 400);
+    assert_eq!(b.current_tick(), 400);
+    assert_eq!(a.states().checksum(), b.states().checksum());
+    assert_eq!(a.desync(), None);
+    assert_eq!(b.desync(), None);
+    // the inputs always arrive before they are needed
+    assert_eq!(a.rollbacks(), 0);
+    assert_eq!(b.rollbacks(), 0);
+    assert_heroes_alive(&peers);
+}
+
+#[test]
+fn late_inputs_are_rolled_back_and_peers_converge() {
+    let conditions = LinkConditions {
+        latency: 3,
+        jitter: 4,
+        loss: 0.2,
+    };
+    let mut peers = connect(conditions, (9, 9), 1);
+    run(&mut peers, 500);
+    let [a, b] = &peers;
+    assert!(a.rollbacks() > 0 && b.rollbacks() > 0);
+    assert_eq!(a.desync(), None);
+    assert_eq!(b.desync(), None);
+
+    // the inputs have been steady for long enough that both are confirmed
+    assert!(a.current_tick() > 300 && a.current_tick() == b.current_tick());
+    assert_eq!(a.states().checksum(), b.states().checksum());
+    assert_heroes_alive(&peers);
+}
+
+#[test]
+fn input_delay_hides_latency() {
+    let conditions = LinkConditions {
+        latency: 2,
+        jitter: 0,
+        loss: 0.0,
+    };
+    let mut delayed = connect(conditions, (9, 9), 3);
+    run(&mut delayed, 300);
+    assert_eq!(delayed[0].rollbacks() + delayed[1].rollbacks(), 0);
+
+    let mut immediate = connect(conditions, (9, 9), 0);
+    run(&mut immediate, 300);
+    assert!(immediate[0].rollbacks() > 0);
+    assert_eq!(
+        immediate[0].states().checksum(),
+        immediate[1].states().checksum()
+    );
+    assert_heroes_alive(&immediate);
+}
+
+#[test]
+fn checksums_reveal_a_desync() {
+    // different seeds: the games differ from the very first tick
+    let mut peers = connect(LinkConditions::PERFECT, (1, 2), 2);
+    run(&mut peers, 30);
+    let desync = peers[0].desync().expect("desync not detected");
+    assert_eq!(desync.tick, 0);
+    assert_ne!(desync.local, desync.remote);
+    assert_eq!(peers[1].desync().map(|desync| desync.tick), Some(0));
+}
+
+#[test]
+fn a_silent_peer_stalls_the_simulation() {
+    let conditions = LinkConditions {
+        latency: 0,
+        jitter: 0,
+        loss: 1.0,
+    };
+    let mut peers = connect(conditions, (9, 9), 2);
+    run(&mut peers, 100);
+    assert_eq!(peers[0].current_tick(), MAX_ROLLBACK);
+    assert_eq!(peers[0].confirmed_tick(), 0);
+}
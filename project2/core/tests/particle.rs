@@ -0,0 +1,81 @@
+use core::{
+    entity::enemy::EnemyKind,
+    event::GameEvent,
+    particle::{EmitterKind, ParticleSystem, MAX_PARTICLES},
+    render::Primitive,
+};
+
+/// Primitives after emitting an explosion and a trail then ticking `ticks` times
+fn burst(seed: u64, ticks: u32) -> Vec<Primitive> {
+    let mut particles = ParticleSystem::new(seed);
+    particles.emit(EmitterKind::Explosion, (100.0, 200.0));
+    particles.emit(EmitterKind::EngineTrail, (50.0, 20.0));
+    for _ in 0..ticks {
+        particles.tick();
+    }
+    particles.render(20).collect()
+}
+
+#[test]
+fn the_same_seed_gives_the_same_particles() {
+    for ticks in [0, 1, 4] {
+        assert!(!burst(3, ticks).is_empty());
+        assert_eq!(burst(3, ticks), burst(3, ticks));
+        assert_ne!(burst(3, ticks), burst(4, ticks));
+    }
+}
+
+#[test]
+fn live_particles_are_capped() {
+    let mut particles = ParticleSystem::new(1);
+    for _ in 0..100 {
+        particles.emit(EmitterKind::Explosion, (0.0, 0.0));
+    }
+    assert_eq!(particles.len(), MAX_PARTICLES);
+
+    let kill = GameEvent::EnemyKilled {
+        kind: EnemyKind::SuperBigCup,
+        pos: (0.0, 0.0),
+        score: 0,
+        player: None,
+    };
+    particles.handle_events(&[kill; 20]);
+    assert_eq!(particles.len(), MAX_PARTICLES);
+    assert_eq!(particles.render(0).count(), MAX_PARTICLES);
+
+    // room is made as they die
+    for _ in 0..20 {
+        particles.tick();
+    }
+    assert!(particles.is_empty());
+    particles.emit(EmitterKind::Explosion, (0.0, 0.0));
+    assert_eq!(particles.len(), 12);
+}
+
+#[test]
+fn particles_fade_out_and_expire() {
+    let mut particles = ParticleSystem::new(9);
+    particles.emit(EmitterKind::Explosion, (0.0, 0.0));
+    let alpha = |particles: &ParticleSystem| {
+        particles
+            .render(0)
+            .map(|primitive| primitive.alpha())
+            .fold(0.0, f32::max)
+    };
+    assert_eq!(alpha(&particles), 1.0);
+
+    // explosions live 6 to 12 ticks
+    let mut last = 1.0;
+    for _ in 0..5 {
+        particles.tick();
+        let faded = alpha(&particles);
+        assert!(faded < last, "{faded} >= {last}");
+        last = faded;
+    }
+    assert!(!particles.is_empty());
+    for _ in 5..12 {
+        particles.tick();
+    }
+    assert!(particles.is_empty());
+    assert_eq!(particles.render(0).count(), 0);
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use common::{advance, game, settings};
+use core::{
+    headless::simulate,
+    replay::{parse_script, RecordedInput},
+};
+
+#[test]
+fn scripts_are_parsed_into_sorted_inputs() {
+    let inputs = parse_script(
+        "
+        # comments and blank lines are skipped
+
+        40 d up   # trailing comment
+        0 space down
+        10 pointer move 12.5 300
+        0 d down
+        ",
+    )
+    .unwrap();
+    let parsed: Vec<_> = inputs
+        .iter()
+        .map(|input| match &input.input {
+            RecordedInput::Key { key, pressed } => (input.tick, key.clone(), *pressed),
+            RecordedInput::Pointer { x, y, .. } => (input.tick, format!("{x},{y}"), true),
+            _ => unreachable!(),
+        })
+        .collect();
+    // sorted by tick, in script order within a tick
+    assert_eq!(
+        parsed,
+        [
+            (0, " ".to_string(), true),
+            (0, "d".to_string(), true),
+            (10, "12.5,300".to_string(), true),
+            (40, "d".to_string(), false),
+        ]
+    );
+}
+
+#[test]
+fn malformed_lines_are_rejected_with_their_number() {
+    for (script, error) in [
+        ("0 d down\nx d down", "line 2: invalid tick"),
+        ("-1 d down", "line 1: invalid tick"),
+        ("0 d press", "line 1: action must be `down` or `up`"),
+        ("0 d", "line 1: expected `<tick> <key> <down|up>`"),
+        ("0 d down now", "line 1: expected `<tick> <key> <down|up>`"),
+        (
+            "0 pointer drag 1 2",
+            "line 1: phase must be `down`, `move` or `up`",
+        ),
+        (
+            "0 pointer down 1",
+            "line 1: expected `<tick> <key> <down|up>`",
+        ),
+        ("0 pointer down x 2", "line 1: invalid x"),
+    ] {
+        assert_eq!(
+            parse_script(script).err().as_deref(),
+            Some(error),
+            "{script}"
+        );
+    }
+}
+
+#[test]
+fn unknown_keys_are_kept_but_do_nothing() {
+    let with_unknown = parse_script("0 space down\n5 F13 down\n9 F13 up").unwrap();
+    assert_eq!(with_unknown.len(), 3);
+    let without = parse_script("0 space down").unwrap();
+
+    let mut game_with = game(5);
+    advance(&mut game_with, &with_unknown, 0, 100);
+    let mut game_without = game(5);
+    advance(&mut game_without, &without, 0, 100);
+    assert_eq!(game_with.checksums(), game_without.checksums());
+}
+
+#[test]
+fn simulations_are_reproducible() {
+    let inputs = parse_script("0 space down\n0 d down\n40 d up").unwrap();
+    let run = |seed| serde_json::to_string(&simulate(settings(seed), &inputs, 500)).unwrap();
+    assert_eq!(run(1), run(1));
+    assert_ne!(run(1), run(2));
+
+    let summary = simulate(settings(1), &inputs, 500);
+    assert_eq!(summary.seed, 1);
+    assert!(summary.ticks_survived <= 500);
+    assert!(summary.bullets_fired > 0);
+    // stops at the first death of the only hero
+    let long = simulate(settings(1), &inputs, 100_000);
+    assert!(long.died);
+    assert!(long.ticks_survived < 100_000);
+}
@@ -0,0 +1,192 @@
+mod common;
+
+use common::advance;
+use core::{
+    audio::{AudioCue, MusicState},
+    input::Action,
+    replay::{parse_script, Replay, ReplayInput},
+    rewind::RewindSettings,
+    Game,
+};
+
+const SCRIPT: &str = "
+0 space down
+0 d down
+45 d up
+45 w down
+70 w up
+150 a down
+230 a up
+260 shift down
+330 shift up
+";
+
+fn new_game() -> Game {
+    common::game(11)
+}
+
+/// The state saved after every tick up to `to`, starting with the initial one
+fn past_states(game: &mut Game, inputs: &[ReplayInput], to: u32) -> Vec<String> {
+    let mut states = vec![game.save_state()];
+    for tick in 0..to {
+        advance(game, inputs, tick, tick + 1);
+        states.push(game.save_state());
+    }
+    states
+}
+
+/// A saved state and, apart, what the heroes hold in it. The inputs fed at
+/// the tick it was saved at, which could only have changed that, are dropped.
+fn split_inputs(state: &str) -> (serde_json::Value, Vec<serde_json::Value>) {
+    let mut state: serde_json::Value = serde_json::from_str(state).unwrap();
+    let tick = state["states"]["stats"]["ticks"].clone();
+    state["inputs"]
+        .as_array_mut()
+        .unwrap()
+        .retain(|input| input["tick"] != tick);
+    let heroes = state["states"]["heroes"].as_array_mut().unwrap();
+    let inputs = heroes
+        .iter_mut()
+        .map(|hero| hero.as_object_mut().unwrap().remove("input").unwrap())
+        .collect();
+    (state, inputs)
+}
+
+#[test]
+fn rewinding_restores_the_exact_past_state() {
+    let inputs = parse_script(SCRIPT).unwrap();
+    let mut original = new_game();
+    let past = past_states(&mut original, &inputs, 300);
+    let (_, held) = split_inputs(&past[300]);
+
+    for ticks in [1, 19, 20, 21, 77, 150] {
+        let mut game = new_game();
+        advance(&mut game, &inputs, 0, 300);
+        assert_eq!(game.rewind(ticks), ticks);
+        // but for the keys, still held as they were before the rewind
+        let (state, inputs) = split_inputs(&game.save_state());
+        assert_eq!(
+            state,
+            split_inputs(&past[300 - ticks as usize]).0,
+            "{ticks} ticks"
+        );
+        // the last pressed direction only matters while both are held
+        for key in ["up", "down", "left", "right", "fire", "focus", "stick"] {
+            assert_eq!(inputs[0][key], held[0][key], "{ticks} ticks, {key}");
+        }
+    }
+}
+
+#[test]
+fn playing_on_after_a_rewind_is_deterministic() {
+    let inputs = parse_script(SCRIPT).unwrap();
+    let mut original = new_game();
+    advance(&mut original, &inputs, 0, 400);
+
+    // the same keys are held at both ends of the rewind
+    let mut game = new_game();
+    advance(&mut game, &inputs, 0, 320);
+    assert_eq!(game.rewind(50), 50);
+    advance(&mut game, &inputs, 270, 400);
+    assert_eq!(game.save_state(), original.save_state());
+    assert_eq!(game.export_replay(), original.export_replay());
+}
+
+#[test]
+fn keys_held_through_a_rewind_stay_held() {
+    let inputs = parse_script(SCRIPT).unwrap();
+    // alive until the end
+    let mut settings = common::settings(11);
+    settings.lives = 100;
+    let mut game = Game::new(settings);
+    advance(&mut game, &inputs, 0, 100);
+    // pressed before the rewind, back to tick 40 when only right was held
+    game.action(0, Action::MoveLeft, true);
+    game.action(0, Action::MoveRight, true);
+    advance(&mut game, &[], 100, 110);
+    assert_eq!(game.rewind(70), 70);
+    let (_, held) = split_inputs(&game.save_state());
+    assert_eq!(held[0]["left"], true);
+    assert_eq!(held[0]["right"], true);
+    // right still wins, it was pressed last
+    assert_eq!(held[0]["last_x"], 1.0);
+
+    // and released after it
+    advance(&mut game, &inputs, 40, 60);
+    game.action(0, Action::MoveLeft, false);
+    advance(&mut game, &inputs, 60, 140);
+    let (_, held) = split_inputs(&game.save_state());
+    assert_eq!(held[0]["left"], false);
+    assert_eq!(held[0]["right"], false);
+    advance(&mut game, &inputs, 140, 200);
+
+    let replay = Replay::from_json(&game.export_replay()).unwrap();
+    assert_eq!(replay.verify(), Ok(200));
+    let mut replayed = Game::new(settings);
+    advance(&mut replayed, &replay.inputs, 0, 200);
+    assert_eq!(replayed.save_state(), game.save_state());
+}
+
+#[test]
+fn history_is_bounded_by_the_settings() {
+    let mut game = new_game();
+    game.set_rewind_settings(RewindSettings::new(10, 5));
+    advance(&mut game, &[], 0, 405);
+    // snapshots at 360, 370, ... 400
+    assert_eq!(game.rewind_available(), 45);
+    assert_eq!(game.rewind(1000), 45);
+    assert_eq!(game.rewind_available(), 0);
+
+    game.set_rewind_settings(RewindSettings::new(10, 0));
+    advance(&mut game, &[], 0, 50);
+    assert_eq!(game.rewind(10), 0);
+}
+
+#[test]
+fn rewinding_out_of_a_game_over_restores_the_music() {
+    let mut game = new_game();
+    while !game.end() {
+        game.tick();
+    }
+    // the last music switch
+    let music = |cues: Vec<AudioCue>| {
+        cues.into_iter().rev().find_map(|cue| match cue {
+            AudioCue::Music { state } => Some(state),
+            _ => None,
+        })
+    };
+    assert_eq!(music(game.take_audio_cues()), Some(MusicState::GameOver));
+
+    assert!(game.rewind(100) > 0);
+    assert!(!game.end());
+    let cues = game.take_audio_cues();
+    assert!(cues.iter().all(|cue| matches!(cue, AudioCue::Music { .. })));
+    assert!(matches!(
+        music(cues),
+        Some(MusicState::Normal | MusicState::Boss)
+    ));
+}
+
+#[test]
+fn rewinding_drops_the_particles_and_popups() {
+    // moving at the end, leaving an engine trail
+    let inputs = parse_script(&format!("{SCRIPT}\n390 d down")).unwrap();
+    // alive and firing until the end
+    let mut settings = common::settings(11);
+    settings.lives = 100;
+    let mut game = Game::new(settings);
+    advance(&mut game, &inputs, 0, 300);
+    let past = game.save_state();
+    advance(&mut game, &inputs, 300, 400);
+    game.prepare_primitives();
+    let with_effects = game.primitives_len();
+
+    assert_eq!(game.rewind(100), 100);
+    game.prepare_primitives();
+    // only the entities are left, as in a game just loaded at that tick
+    let mut loaded = new_game();
+    loaded.load_state(&past).unwrap();
+    loaded.prepare_primitives();
+    assert_eq!(game.primitives_len(), loaded.primitives_len());
+    assert!(game.primitives_len() < with_effects);
+}
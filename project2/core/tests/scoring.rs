@@ -0,0 +1,142 @@
+use core::{
+    entity::{enemy::EnemyKind, hero::Hero},
+    event::GameEvent,
+    scoring::{
+        kill_points, ScoreReason, Scoring, CHAIN_STEP, CHAIN_WINDOW, GRAZE_POINTS, MAX_MULTIPLIER,
+        WAVE_BONUS,
+    },
+};
+
+fn kill(kind: EnemyKind, player: u8) -> GameEvent {
+    GameEvent::EnemyKilled {
+        kind,
+        pos: (10.0, 20.0),
+        score: kill_points(kind),
+        player: Some(player),
+    }
+}
+
+fn heroes() -> Vec<Hero> {
+    vec![Hero::new(0, 0.0, 0.0, 3), Hero::new(1, 100.0, 0.0, 3)]
+}
+
+/// Award the events of one tick, returns the points and the awards
+fn tick(
+    scoring: &mut Scoring,
+    heroes: &mut [Hero],
+    events: &[GameEvent],
+) -> (u32, Vec<(u8, u32, ScoreReason)>) {
+    let mut events = events.to_vec();
+    let total = scoring.award(&mut events, heroes);
+    let awards = events
+        .iter()
+        .filter_map(|event| match *event {
+            GameEvent::ScoreAwarded {
+                player,
+                points,
+                reason,
+                ..
+            } => Some((player, points, reason)),
+            _ => None,
+        })
+        .collect();
+    (total, awards)
+}
+
+#[test]
+fn kills_are_worth_the_points_of_their_kind() {
+    assert_eq!(kill_points(EnemyKind::MiddleCup), 100);
+    assert_eq!(kill_points(EnemyKind::BigCup), 600);
+    assert_eq!(kill_points(EnemyKind::SuperBigCup), 10000);
+
+    let (mut scoring, mut heroes) = (Scoring::default(), heroes());
+    let (total, awards) = tick(
+        &mut scoring,
+        &mut heroes,
+        &[kill(EnemyKind::BigCup, 1), kill(EnemyKind::MiddleCup, 0)],
+    );
+    assert_eq!(total, 700);
+    assert_eq!(
+        awards,
+        vec![(1, 600, ScoreReason::Kill), (0, 100, ScoreReason::Kill)]
+    );
+    assert_eq!((heroes[0].score, heroes[1].score), (100, 600));
+}
+
+#[test]
+fn chains_multiply_and_break() {
+    let (mut scoring, mut heroes) = (Scoring::default(), heroes());
+    let mut points = vec![];
+    for _ in 0..CHAIN_STEP * 2 + 1 {
+        points.push(tick(&mut scoring, &mut heroes, &[kill(EnemyKind::MiddleCup, 0)]).0);
+    }
+    assert_eq!(points[0], 100);
+    assert_eq!(points[CHAIN_STEP as usize], 200);
+    assert_eq!(points[CHAIN_STEP as usize * 2], 300);
+    assert_eq!(scoring.chain(0).multiplier(), 3);
+    // the other player has their own chain
+    assert_eq!(scoring.chain(1).multiplier(), 1);
+
+    // being hit breaks it
+    let hit = GameEvent::HeroHit {
+        player: 0,
+        pos: (0.0, 0.0),
+    };
+    tick(&mut scoring, &mut heroes, &[hit]);
+    assert_eq!(scoring.chain(0).kills, 0);
+    assert_eq!(
+        tick(&mut scoring, &mut heroes, &[kill(EnemyKind::MiddleCup, 0)]).0,
+        100
+    );
+
+    // and so does waiting too long
+    for _ in 0..CHAIN_WINDOW {
+        tick(&mut scoring, &mut heroes, &[]);
+    }
+    assert_eq!(scoring.chain(0).kills, 0);
+
+    let mut last = 0;
+    for _ in 0..1000 {
+        last = tick(&mut scoring, &mut heroes, &[kill(EnemyKind::MiddleCup, 0)]).0;
+    }
+    assert_eq!(last, 100 * MAX_MULTIPLIER);
+}
+
+#[test]
+fn grazes_and_waves_earn_bonuses() {
+    let (mut scoring, mut heroes) = (Scoring::default(), heroes());
+    let graze = GameEvent::NearMiss {
+        player: 1,
+        pos: (0.0, 0.0),
+    };
+    let (total, awards) = tick(&mut scoring, &mut heroes, &[graze]);
+    assert_eq!(total, GRAZE_POINTS);
+    assert_eq!(awards, vec![(1, GRAZE_POINTS, ScoreReason::Graze)]);
+
+    // nothing for the start of the first wave
+    let (total, _) = tick(
+        &mut scoring,
+        &mut heroes,
+        &[GameEvent::WaveStarted { wave: 1 }],
+    );
+    assert_eq!(total, 0);
+    let (total, awards) = tick(
+        &mut scoring,
+        &mut heroes,
+        &[GameEvent::WaveStarted { wave: 3 }],
+    );
+    assert_eq!(total, WAVE_BONUS * 2);
+    assert_eq!(
+        awards,
+        vec![
+            (0, WAVE_BONUS, ScoreReason::WaveBonus),
+            (1, WAVE_BONUS, ScoreReason::WaveBonus)
+        ]
+    );
+    let (_, awards) = tick(
+        &mut scoring,
+        &mut heroes,
+        &[GameEvent::WaveStarted { wave: 4 }],
+    );
+    assert_eq!(awards[0], (0, WAVE_BONUS * 3, ScoreReason::WaveBonus));
+}
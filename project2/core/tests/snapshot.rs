@@ -0,0 +1,123 @@
+mod common;
+
+use common::{advance, game};
+use core::{
+    audio::{AudioCue, MusicState},
+    replay::parse_script,
+    snapshot::Snapshot,
+    Game,
+};
+
+const SCRIPT: &str = "
+0 space down
+0 d down
+40 d up
+40 w down
+60 w up
+200 a down
+260 a up
+300 shift down
+420 shift up
+";
+
+#[test]
+fn continuing_from_a_snapshot_matches_the_original() {
+    let inputs = parse_script(SCRIPT).unwrap();
+    for split in [1, 50, 250, 400] {
+        let mut original = game(7);
+        advance(&mut original, &inputs, 0, split);
+        let snapshot = original.save_state();
+
+        // a game with another seed, so that nothing but the snapshot carries over
+        let mut restored = game(8);
+        restored.load_state(&snapshot).unwrap();
+        assert_eq!(restored.save_state(), snapshot);
+
+        advance(&mut original, &inputs, split, 600);
+        advance(&mut restored, &inputs, split, 600);
+        assert_eq!(restored.score(), original.score());
+        assert_eq!(restored.end(), original.end());
+        assert_eq!(
+            restored.save_state(),
+            original.save_state(),
+            "split at {split}"
+        );
+        assert_eq!(restored.export_replay(), original.export_replay());
+    }
+}
+
+#[test]
+fn snapshots_keep_animation_progress_and_rng() {
+    let inputs = parse_script(SCRIPT).unwrap();
+    let mut game = game(3);
+    advance(&mut game, &inputs, 0, 300);
+    let json = game.save_state();
+    assert_eq!(Snapshot::from_json(&json).unwrap().to_json(), json);
+
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(value["states"]["stats"]["ticks"], 300);
+    let hero = &value["states"]["heroes"][0];
+    assert_eq!(hero["animator"]["set"], "hero");
+    assert!(hero["animator"]["player"]["elapsed"].is_number());
+    assert!(value["states"]["rng"].is_object());
+}
+
+#[test]
+fn unsupported_or_broken_snapshots_are_rejected() {
+    let mut game = game(1);
+    advance(&mut game, &[], 0, 10);
+    let before = game.save_state();
+
+    let mut value: serde_json::Value = serde_json::from_str(&before).unwrap();
+    value["version"] = 0.into();
+    let err = game.load_state(&value.to_string()).unwrap_err();
+    assert!(err.contains("version"), "{err}");
+
+    value["version"] = 1.into();
+    value["states"]["heroes"][0]["animator"]["clip"] = "nope".into();
+    assert!(game.load_state(&value.to_string()).is_err());
+
+    assert!(game.load_state("{").is_err());
+    assert_eq!(game.save_state(), before);
+}
+
+#[test]
+fn snapshots_of_another_player_count_are_rejected() {
+    let mut coop = common::settings(1);
+    coop.players = 2;
+    let mut coop = Game::new(coop);
+    let mut solo = game(1);
+    advance(&mut solo, &[], 0, 10);
+    let before = solo.save_state();
+
+    let err = solo.load_state(&coop.save_state()).unwrap_err();
+    assert!(err.contains("2 players"), "{err}");
+    let err = coop.load_state(&before).unwrap_err();
+    assert!(err.contains("1 players"), "{err}");
+
+    // players and heroes disagree
+    let mut value: serde_json::Value = serde_json::from_str(&before).unwrap();
+    value["settings"]["players"] = 2.into();
+    let err = coop.load_state(&value.to_string()).unwrap_err();
+    assert!(err.contains("1 heroes for 2 players"), "{err}");
+    assert_eq!(solo.save_state(), before);
+}
+
+#[test]
+fn loading_a_snapshot_resyncs_the_music() {
+    let mut alive = game(2);
+    advance(&mut alive, &[], 0, 10);
+    let mut over = game(2);
+    while !over.end() {
+        over.tick();
+    }
+    over.take_audio_cues();
+
+    over.load_state(&alive.save_state()).unwrap();
+    assert_eq!(
+        over.take_audio_cues(),
+        [AudioCue::Music {
+            state: MusicState::Normal
+        }]
+    );
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use common::game;
+use core::{
+    clock::{Clock, MAX_TIME_SCALE, MIN_TIME_SCALE, MS_PER_TICK, SLOW_MOTION_SCALE},
+    headless,
+    replay::parse_script,
+    Game,
+};
+
+const SCRIPT: &str = "
+0 space down
+0 d down
+30 d up
+30 a down
+90 a up
+120 w down
+140 w up
+";
+
+const TICKS: u32 = 600;
+const SEED: u64 = 7;
+
+/// Play the script through [`Game::advance`] with frames of `frame_ms`, short
+/// enough for at most one tick per frame so inputs land on their tick
+fn play_scaled(scale: f32, frame_ms: f64) -> Game {
+    let mut game = game(SEED);
+    game.set_time_scale(scale);
+    let inputs = parse_script(SCRIPT).unwrap();
+    let mut inputs = inputs.iter().peekable();
+    while (game.checksums().len() as u32) < TICKS && !game.end() {
+        let tick = game.checksums().len() as u32;
+        while let Some(input) = inputs.next_if(|input| input.tick <= tick) {
+            input.apply(&mut game);
+        }
+        assert!(game.advance(frame_ms) <= 1);
+    }
+    game
+}
+
+#[test]
+fn results_do_not_depend_on_the_time_scale() {
+    let mut reference = game(SEED);
+    headless::run(
+        &mut reference,
+        &parse_script(SCRIPT).unwrap(),
+        TICKS,
+        |_| (),
+    );
+
+    for scale in [0.25, 1.0, 4.0] {
+        let game = play_scaled(scale, 10.0);
+        assert_eq!(game.checksums(), reference.checksums(), "scale {scale}");
+        assert_eq!(game.score(), reference.score());
+    }
+}
+
+#[test]
+fn the_scale_sets_ticks_per_real_second() {
+    for (scale, ticks) in [(0.5, 10), (1.0, 20), (2.0, 40)] {
+        let mut game = game(SEED);
+        game.set_time_scale(scale);
+        let ran: u32 = (0..100).map(|_| game.advance(10.0)).sum();
+        assert_eq!(ran, ticks, "scale {scale}");
+    }
+
+    let mut clock = Clock::default();
+    clock.set_scale(100.0);
+    assert_eq!(clock.scale(), MAX_TIME_SCALE);
+    clock.set_scale(0.0);
+    assert_eq!(clock.scale(), MIN_TIME_SCALE);
+    for scale in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+        clock.set_scale(scale);
+        assert_eq!(clock.scale(), MIN_TIME_SCALE);
+    }
+    let mut game = game(SEED);
+    game.set_time_scale(f32::NAN);
+    assert_eq!(game.time_scale(), 1.0);
+    assert_eq!(game.advance(100.0), 2);
+
+    // a long stall is not caught up all at once
+    let mut clock = Clock::default();
+    assert!(clock.advance(60_000.0) < 20);
+    assert!(clock.ms_delta() < MS_PER_TICK as u128);
+
+    // nor is a broken frame time
+    for elapsed_ms in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+        assert_eq!(clock.advance(elapsed_ms), 0);
+    }
+    assert_eq!(clock.advance(MS_PER_TICK), 1);
+}
+
+#[test]
+fn slow_motion_slows_down_then_recovers() {
+    let mut game = game(SEED);
+    game.slow_motion(40);
+    assert_eq!(game.time_scale(), SLOW_MOTION_SCALE);
+    // 20 ticks of real time
+    let ran: u32 = (0..100).map(|_| game.advance(10.0)).sum();
+    assert_eq!(ran, 5);
+
+    // back to speed once the slow motion ticks ran, easing in at the end
+    let mut eased = false;
+    while game.time_scale() < 1.0 {
+        eased |= game.time_scale() > SLOW_MOTION_SCALE;
+        game.advance(10.0);
+    }
+    assert!(eased);
+    assert_eq!(game.checksums().len(), 40);
+}
@@ -0,0 +1,79 @@
+// Plays the audio cues drained from the core.
+// There is no sound asset yet, so every cue is synthesized with WebAudio.
+
+const SOUNDS = {
+  shot: { type: "square", freq: 880, to: 440, duration: 0.05, gain: 0.05 },
+  enemy_hit: { type: "square", freq: 320, to: 200, duration: 0.05, gain: 0.06 },
+  small_explosion: { type: "sawtooth", freq: 200, to: 40, duration: 0.2, gain: 0.12 },
+  big_explosion: { type: "sawtooth", freq: 160, to: 30, duration: 0.4, gain: 0.16 },
+  boss_explosion: { type: "sawtooth", freq: 120, to: 20, duration: 0.9, gain: 0.2 },
+  boss_phase: { type: "triangle", freq: 220, to: 660, duration: 0.3, gain: 0.12 },
+  hero_hit: { type: "sawtooth", freq: 440, to: 110, duration: 0.3, gain: 0.2 },
+  hero_death: { type: "sawtooth", freq: 330, to: 30, duration: 1.0, gain: 0.2 },
+  pickup: { type: "sine", freq: 660, to: 1320, duration: 0.15, gain: 0.1 },
+  wave_start: { type: "triangle", freq: 440, to: 880, duration: 0.25, gain: 0.1 },
+};
+
+// base frequency of the background drone, null means silence
+const MUSIC = {
+  normal: 110,
+  boss: 73.42,
+  game_over: null,
+};
+
+export class AudioPlayer {
+  constructor() {
+    this.ctx = new AudioContext();
+    this.music = null;
+  }
+
+  play(cues) {
+    for (const cue of cues) {
+      if (cue.type == "sound") {
+        this.playSound(SOUNDS[cue.cue]);
+      } else if (cue.type == "music") {
+        this.playMusic(MUSIC[cue.state]);
+      }
+    }
+  }
+
+  playSound(sound) {
+    if (!sound) return;
+    const now = this.ctx.currentTime;
+    const osc = this.ctx.createOscillator();
+    const gain = this.ctx.createGain();
+    osc.type = sound.type;
+    osc.frequency.setValueAtTime(sound.freq, now);
+    osc.frequency.exponentialRampToValueAtTime(sound.to, now + sound.duration);
+    gain.gain.setValueAtTime(sound.gain, now);
+    gain.gain.exponentialRampToValueAtTime(0.001, now + sound.duration);
+    osc.connect(gain).connect(this.ctx.destination);
+    osc.start(now);
+    osc.stop(now + sound.duration);
+  }
+
+  playMusic(freq) {
+    this.stopMusic();
+    if (freq == null) return;
+    const osc = this.ctx.createOscillator();
+    const gain = this.ctx.createGain();
+    osc.type = "triangle";
+    osc.frequency.value = freq;
+    gain.gain.value = 0.03;
+    osc.connect(gain).connect(this.ctx.destination);
+    osc.start();
+    this.music = osc;
+  }
+
+  stopMusic() {
+    if (this.music) {
+      this.music.stop();
+      this.music = null;
+    }
+  }
+
+  close() {
+    this.stopMusic();
+    this.ctx.close();
+  }
+}
@@ -1,5 +1,7 @@
-export async function load_image_bitmap(name) {
-    const image_response = await fetch(`./images/${name}`);
-    const image_blob = await image_response.blob();
-    return await createImageBitmap(image_blob);
-}
\ No newline at end of file
+// atlas.json and atlas.png are generated by the `atlas` tool, see `core/justfile`
+export async function load_atlas() {
+    const manifest = await (await fetch("./atlas.json")).json();
+    const image_response = await fetch(`./${manifest.image}`);
+    const image = await createImageBitmap(await image_response.blob());
+    return { image, sprites: manifest.sprites };
+}